@@ -0,0 +1,361 @@
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::Result;
+use fs_err as fs;
+use itertools::Itertools;
+use serde::Serialize;
+use strum::EnumCount;
+use strum::IntoEnumIterator;
+use triton_vm::aet::AlgebraicExecutionTrace;
+use triton_vm::air::table::hash::HashTableMode;
+use triton_vm::air::table_column::CascadeMainColumn;
+use triton_vm::air::table_column::HashMainColumn;
+use triton_vm::air::table_column::JumpStackMainColumn;
+use triton_vm::air::table_column::LookupMainColumn;
+use triton_vm::air::table_column::MasterMainColumn;
+use triton_vm::air::table_column::OpStackMainColumn;
+use triton_vm::air::table_column::ProcessorMainColumn;
+use triton_vm::air::table_column::RamMainColumn;
+use triton_vm::air::table_column::U32MainColumn;
+use triton_vm::isa::instruction::Instruction;
+use triton_vm::prelude::BFieldElement;
+use triton_vm::prelude::TableId;
+use triton_vm::prelude::bfe;
+use triton_vm::table::u32::U32TableEntry;
+use triton_vm::twenty_first::math::traits::Inverse;
+use triton_vm::twenty_first::tip5;
+
+use crate::args::AetFormat;
+
+// Triton VM fills its tables from the algebraic execution trace internally,
+// without exposing how. The tables that are not part of the trace as is are
+// therefore re-derived here. Should Triton VM change the layout of any of them,
+// these assertions fail, and test `aet_tables_have_triton_vm_heights` checks
+// that the number of rows keeps matching.
+const _: () = assert!(JumpStackMainColumn::COUNT == 5);
+const _: () = assert!(HashMainColumn::COUNT == 67);
+const _: () = assert!(CascadeMainColumn::COUNT == 6);
+const _: () = assert!(LookupMainColumn::COUNT == 4);
+const _: () = assert!(U32MainColumn::COUNT == 10);
+
+/// One table of the [algebraic execution trace](AlgebraicExecutionTrace),
+/// without padding.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Table {
+    pub id: TableId,
+    pub name: &'static str,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<BFieldElement>>,
+}
+
+/// Where a [`Table`] was written to.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct TableFile {
+    pub table: &'static str,
+    pub file: String,
+    pub columns: Vec<String>,
+    pub num_rows: usize,
+}
+
+/// The height of one of Triton VM's tables, before padding.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct TableHeight {
+    pub table: String,
+    pub height: usize,
+}
+
+/// The heights of all of Triton VM's tables, before padding. The tallest table
+/// determines the padded height.
+pub fn heights(aet: &AlgebraicExecutionTrace) -> Vec<TableHeight> {
+    TableId::iter()
+        .map(|id| TableHeight {
+            table: id.to_string(),
+            height: aet.height_of_table(id),
+        })
+        .collect()
+}
+
+/// All tables except the program table, which is the program itself.
+pub fn tables(aet: &AlgebraicExecutionTrace) -> Vec<Table> {
+    vec![
+        Table {
+            id: TableId::Processor,
+            name: "processor",
+            columns: column_names::<ProcessorMainColumn>(),
+            rows: rows(aet.processor_trace.rows()),
+        },
+        Table {
+            id: TableId::OpStack,
+            name: "op_stack",
+            columns: column_names::<OpStackMainColumn>(),
+            rows: rows(aet.op_stack_underflow_trace.rows()),
+        },
+        Table {
+            id: TableId::Ram,
+            name: "ram",
+            columns: column_names::<RamMainColumn>(),
+            rows: rows(aet.ram_trace.rows()),
+        },
+        jump_stack_table(aet),
+        hash_table(aet),
+        cascade_table(aet),
+        lookup_table(aet),
+        u32_table(aet),
+    ]
+}
+
+fn column_names<C: IntoEnumIterator + ToString>() -> Vec<String> {
+    C::iter().map(|column| column.to_string()).collect()
+}
+
+/// The rows of a two-dimensional array of base field elements.
+fn rows<'a, Row>(trace: impl IntoIterator<Item = Row>) -> Vec<Vec<BFieldElement>>
+where
+    Row: IntoIterator<Item = &'a BFieldElement>,
+{
+    trace
+        .into_iter()
+        .map(|row| row.into_iter().copied().collect())
+        .collect()
+}
+
+/// Like in the master table, the jump stack table contains the same rows as
+/// the processor table, sorted by jump stack pointer first, cycle count second.
+fn jump_stack_table(aet: &AlgebraicExecutionTrace) -> Table {
+    let columns = [
+        (JumpStackMainColumn::CLK, ProcessorMainColumn::CLK),
+        (JumpStackMainColumn::CI, ProcessorMainColumn::CI),
+        (JumpStackMainColumn::JSP, ProcessorMainColumn::JSP),
+        (JumpStackMainColumn::JSO, ProcessorMainColumn::JSO),
+        (JumpStackMainColumn::JSD, ProcessorMainColumn::JSD),
+    ];
+    let rows = rows(aet.processor_trace.rows())
+        .into_iter()
+        .map(|processor_row| {
+            let mut row = vec![bfe!(0); JumpStackMainColumn::COUNT];
+            for (column, processor_column) in columns {
+                row[column.main_index()] = processor_row[processor_column.main_index()];
+            }
+            row
+        })
+        // the processor's rows are sorted by cycle count, and sorting is stable
+        .sorted_by_key(|row| row[JumpStackMainColumn::JSP.main_index()].value())
+        .collect();
+
+    Table {
+        id: TableId::JumpStack,
+        name: "jump_stack",
+        columns: column_names::<JumpStackMainColumn>(),
+        rows,
+    }
+}
+
+/// Like in the master table, the hash table consists of the rows for program
+/// hashing, followed by those for the Sponge instructions, followed by those
+/// for instruction `hash`.
+fn hash_table(aet: &AlgebraicExecutionTrace) -> Table {
+    let parts = [
+        (
+            rows(aet.program_hash_trace.rows()),
+            HashTableMode::ProgramHashing,
+        ),
+        (rows(aet.sponge_trace.rows()), HashTableMode::Sponge),
+        (rows(aet.hash_trace.rows()), HashTableMode::Hash),
+    ];
+    let rows = parts
+        .into_iter()
+        .flat_map(|(rows, mode)| {
+            rows.into_iter().map(move |mut row| {
+                row[HashMainColumn::Mode.main_index()] = mode.into();
+                row
+            })
+        })
+        .collect();
+
+    Table {
+        id: TableId::Hash,
+        name: "hash",
+        columns: column_names::<HashMainColumn>(),
+        rows,
+    }
+}
+
+fn cascade_table(aet: &AlgebraicExecutionTrace) -> Table {
+    let lookup = |limb: u16| BFieldElement::from(tip5::LOOKUP_TABLE[usize::from(limb)]);
+    let rows = aet
+        .cascade_table_lookup_multiplicities
+        .iter()
+        .map(|(&look_in, &multiplicity)| {
+            let (look_in_hi, look_in_lo) = (look_in >> 8, look_in & 0xff);
+            let mut row = vec![bfe!(0); CascadeMainColumn::COUNT];
+            row[CascadeMainColumn::LookInHi.main_index()] = look_in_hi.into();
+            row[CascadeMainColumn::LookInLo.main_index()] = look_in_lo.into();
+            row[CascadeMainColumn::LookOutHi.main_index()] = lookup(look_in_hi);
+            row[CascadeMainColumn::LookOutLo.main_index()] = lookup(look_in_lo);
+            row[CascadeMainColumn::LookupMultiplicity.main_index()] = multiplicity.into();
+            row
+        })
+        .collect();
+
+    Table {
+        id: TableId::Cascade,
+        name: "cascade",
+        columns: column_names::<CascadeMainColumn>(),
+        rows,
+    }
+}
+
+fn lookup_table(aet: &AlgebraicExecutionTrace) -> Table {
+    let rows = (0..)
+        .zip(tip5::LOOKUP_TABLE)
+        .zip(aet.lookup_table_lookup_multiplicities)
+        .map(|((look_in, look_out), multiplicity): ((u64, u8), u64)| {
+            let mut row = vec![bfe!(0); LookupMainColumn::COUNT];
+            row[LookupMainColumn::LookIn.main_index()] = look_in.into();
+            row[LookupMainColumn::LookOut.main_index()] = look_out.into();
+            row[LookupMainColumn::LookupMultiplicity.main_index()] = multiplicity.into();
+            row
+        })
+        .collect();
+
+    Table {
+        id: TableId::Lookup,
+        name: "lookup",
+        columns: column_names::<LookupMainColumn>(),
+        rows,
+    }
+}
+
+/// Like in the master table, every distinct call to the U32 co-processor gets
+/// its own section of rows, in the order of the first call.
+fn u32_table(aet: &AlgebraicExecutionTrace) -> Table {
+    let rows = aet
+        .u32_entries
+        .iter()
+        .flat_map(|(entry, &multiplicity)| u32_section(entry, multiplicity))
+        .collect();
+
+    Table {
+        id: TableId::U32,
+        name: "u32",
+        columns: column_names::<U32MainColumn>(),
+        rows,
+    }
+}
+
+/// The rows for one call to the U32 co-processor. Every row shifts the operands
+/// by one bit until they are used up. The result is then accumulated from the
+/// last row to the first, which holds the result of the call.
+fn u32_section(entry: &U32TableEntry, multiplicity: u64) -> Vec<Vec<BFieldElement>> {
+    use U32MainColumn::*;
+
+    let instruction = entry.instruction;
+    let is_pow = instruction == Instruction::Pow;
+    let mut row = vec![bfe!(0); U32MainColumn::COUNT];
+    row[CopyFlag.main_index()] = bfe!(1);
+    row[CI.main_index()] = instruction.opcode_b();
+    row[LHS.main_index()] = entry.left_operand;
+    row[RHS.main_index()] = entry.right_operand;
+    row[LookupMultiplicity.main_index()] = multiplicity.into();
+
+    let mut section = vec![];
+    loop {
+        let bits = row[Bits.main_index()];
+        let lhs = row[LHS.main_index()];
+        let rhs = row[RHS.main_index()];
+        row[BitsMinus33Inv.main_index()] = (bits - bfe!(33)).inverse();
+        row[LhsInv.main_index()] = lhs.inverse_or_zero();
+        row[RhsInv.main_index()] = rhs.inverse_or_zero();
+        section.push(row.clone());
+        if (lhs.value() == 0 || is_pow) && rhs.value() == 0 {
+            break;
+        }
+
+        row[CopyFlag.main_index()] = bfe!(0);
+        row[Bits.main_index()] = bits + bfe!(1);
+        if !is_pow {
+            row[LHS.main_index()] = bfe!(lhs.value() / 2);
+        }
+        row[RHS.main_index()] = bfe!(rhs.value() / 2);
+        row[LookupMultiplicity.main_index()] = bfe!(0);
+    }
+
+    let last = section.len() - 1;
+    section[last][Result.main_index()] = match instruction {
+        // instruction `lt` on operands 0 and 0 is known to be false
+        Instruction::Lt if last == 0 => bfe!(0),
+        Instruction::Lt => bfe!(2),
+        Instruction::Log2Floor => bfe!(-1),
+        Instruction::Pow => bfe!(1),
+        _ => bfe!(0),
+    };
+    for i in (0..last).rev() {
+        let (row, next_row) = (&section[i], &section[i + 1]);
+        let lhs = row[LHS.main_index()];
+        let lhs_lsb = bfe!(lhs.value() % 2);
+        let rhs_lsb = bfe!(row[RHS.main_index()].value() % 2);
+        let next_result = next_row[Result.main_index()];
+        let result = match instruction {
+            Instruction::Lt => match (next_result.value(), lhs_lsb.value(), rhs_lsb.value()) {
+                (0 | 1, _, _) => next_result,
+                (_, 0, 1) => bfe!(1),
+                (_, 1, 0) => bfe!(0),
+                _ if row[CopyFlag.main_index()].value() == 1 => bfe!(0),
+                _ => bfe!(2),
+            },
+            Instruction::And => bfe!(2) * next_result + lhs_lsb * rhs_lsb,
+            Instruction::Log2Floor if lhs.value() == 0 => bfe!(-1),
+            Instruction::Log2Floor if next_row[LHS.main_index()].value() != 0 => next_result,
+            Instruction::Log2Floor => row[Bits.main_index()],
+            Instruction::Pow if rhs_lsb.value() == 0 => next_result * next_result,
+            Instruction::Pow => next_result * next_result * lhs,
+            Instruction::PopCount => next_result + lhs_lsb,
+            _ => next_result,
+        };
+        section[i][Result.main_index()] = result;
+    }
+
+    section
+}
+
+/// Write every table to a file in the directory, which is created if needed.
+pub fn write(tables: &[Table], dir: &Path, format: AetFormat) -> Result<Vec<TableFile>> {
+    fs::create_dir_all(dir)?;
+
+    let mut files = Vec::with_capacity(tables.len());
+    for table in tables {
+        let extension = match format {
+            AetFormat::Csv => "csv",
+            AetFormat::Binary => "bin",
+        };
+        let path = dir.join(format!("{}.{extension}", table.name));
+        let mut writer = std::io::BufWriter::new(fs::File::create(&path)?);
+        match format {
+            AetFormat::Csv => {
+                writeln!(writer, "{}", table.columns.iter().join(","))?;
+                for row in &table.rows {
+                    writeln!(writer, "{}", row.iter().map(|e| e.value()).join(","))?;
+                }
+            }
+            AetFormat::Binary => {
+                for element in table.rows.iter().flatten() {
+                    writer.write_all(&element.value().to_le_bytes())?;
+                }
+            }
+        }
+        writer.flush()?;
+
+        files.push(TableFile {
+            table: table.name,
+            file: path.to_string_lossy().into_owned(),
+            columns: table.columns.clone(),
+            num_rows: table.rows.len(),
+        });
+    }
+
+    let manifest = fs::File::create(dir.join("manifest.json"))?;
+    serde_json::to_writer_pretty(manifest, &files)?;
+
+    Ok(files)
+}
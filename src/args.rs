@@ -1,16 +1,41 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::io::IsTerminal;
+use std::io::Read;
+use std::io::Write;
+
+use anyhow::Context;
 use anyhow::Result;
-use anyhow::anyhow;
 use anyhow::bail;
 use fs_err as fs;
+use itertools::Itertools;
+use serde::Deserialize;
+use serde::Serialize;
+use triton_vm::prelude::BFieldElement;
 use triton_vm::prelude::Claim;
+use triton_vm::prelude::Digest;
 use triton_vm::prelude::NonDeterminism;
 use triton_vm::prelude::Program;
 use triton_vm::prelude::Proof;
 use triton_vm::prelude::PublicInput;
+use triton_vm::prelude::Stark;
 use triton_vm::prelude::VMState;
 
+use crate::bench;
+use crate::container::Header;
+use crate::container::MalformedProof;
+use crate::container::ProofContainer;
+use crate::container::UnsupportedVersion;
+use crate::diagnostic::Diagnostic;
+use crate::input;
+
+/// The file name that stands for stdin when reading, and for stdout when
+/// writing.
+pub const STDIO: &str = "-";
+
 #[derive(Debug, Clone, Eq, PartialEq, clap::Parser)]
 #[command(version, about)]
+#[non_exhaustive]
 pub struct Args {
     #[command(flatten)]
     pub flags: Flags,
@@ -27,7 +52,23 @@ pub enum Command {
     /// the given input (inline or from a file) and (optional) non-determinism.
     /// If the program does not terminate gracefully, the corresponding error is
     /// printed to stderr.
-    Run(RunArgs),
+    Run {
+        #[command(flatten)]
+        args: RunArgs,
+
+        #[command(flatten)]
+        output: OutputArgs,
+
+        /// Record which instructions and labels are executed, and which way
+        /// instructions “skiz” and “recurse_or_return” go. Write the result to
+        /// this file, as an LCOV tracefile that refers to the lines of the
+        /// program's source. The file is also written if Triton VM crashes.
+        ///
+        /// Requires the program to be read from a file. Conflicts with “initial
+        /// state”.
+        #[arg(long, conflicts_with = "initial_state", value_name = "lcov file")]
+        coverage: Option<String>,
+    },
 
     /// Produce a STARK proof and a corresponding claim, attesting to the correct
     /// execution of a Triton VM program.
@@ -43,20 +84,416 @@ pub enum Command {
         #[command(flatten)]
         args: RunArgs,
 
+        #[command(flatten)]
+        stark: StarkArgs,
+
         #[command(flatten)]
         artifacts: ProofArtifacts,
     },
 
     /// Verify a (Claim, Proof)-pair about the correct execution of a Triton VM
     /// program.
-    Verify(ProofArtifacts),
+    ///
+    /// Optionally, also check that the claim is about some expected program, input,
+    /// and output.
+    Verify {
+        #[command(flatten)]
+        artifacts: ProofArtifacts,
+
+        #[command(flatten)]
+        expectations: Expectations,
+
+        /// Verify many (Claim, Proof)-pairs in parallel instead of a single one.
+        ///
+        /// Either a directory or a manifest file. In a directory, every file
+        /// ending in “.proof” is verified. Proofs in the legacy format use the
+        /// claim and parameters files of the same name but ending in “.claim” and
+        /// “.parameters”, respectively. A manifest is a json-encoded list of
+        /// objects with a field “proof” and optional fields “claim” and
+        /// “parameters”, defaulting like in a directory. Relative paths in a
+        /// manifest are relative to the manifest.
+        ///
+        /// Fails if any of the pairs fails verification.
+        #[arg(
+            long,
+            value_name = "dir or manifest",
+            conflicts_with = "claim",
+            conflicts_with = "proof",
+            conflicts_with = "parameters"
+        )]
+        batch: Option<String>,
+    },
+
+    /// Estimate the cost of proving the correct execution of a Triton VM program,
+    /// without proving.
+    ///
+    /// Uses the same program, input, non-determinism, and STARK parameters as
+    /// command “prove”. Prints the padded height, the table that determines it,
+    /// the length of the FRI domain, the expected size of the proof, and a rough
+    /// estimate of the memory the prover needs at its peak. Executing the program
+    /// is much cheaper than proving, but still takes some time for long-running
+    /// programs.
+    Estimate {
+        #[command(flatten)]
+        args: RunArgs,
+
+        #[command(flatten)]
+        stark: StarkArgs,
+    },
+
+    /// Measure how long proving takes, and how much memory it needs, for
+    /// increasing padded heights.
+    ///
+    /// Proves a built-in program, which runs long enough to reach the desired
+    /// padded height, for every power of two in the given range, starting with
+    /// the smallest. Prints the time and the memory proving took as soon as it
+    /// is done. Before proving, the memory the prover needs is estimated like
+    /// command “estimate” does. The benchmark stops before the first padded
+    /// height that needs more memory than is available.
+    Bench(BenchArgs),
+
+    /// Record the state of Triton VM in every cycle of a program's execution.
+    ///
+    /// Uses the same program, input, and non-determinism as command “run”. For
+    /// every cycle, the cycle count, the instruction pointer, the current
+    /// instruction, the depth of the jump stack, and the depth and top of the op
+    /// stack are recorded, as of right before the instruction is executed. If
+    /// Triton VM crashes, the trace ends with the failing instruction.
+    Trace {
+        #[command(flatten)]
+        args: RunArgs,
+
+        #[command(flatten)]
+        trace: TraceArgs,
+    },
+
+    /// Export the tables of the algebraic execution trace of a Triton VM program.
+    ///
+    /// Uses the same program, input, and non-determinism as command “run”. Writes
+    /// the processor, op stack, RAM, jump stack, hash, cascade, lookup, and U32
+    /// tables to a directory, one file per table, without padding. File
+    /// “manifest.json” lists the files, their columns, and their number of rows.
+    /// Also prints the height of every table, which is useful for finding out
+    /// what drives the padded height.
+    Aet {
+        #[command(flatten)]
+        args: RunArgs,
+
+        /// The directory to write the tables to. Created if it does not exist.
+        #[arg(long, value_name = "dir", default_value_t = String::from("aet"))]
+        aet_dir: String,
+
+        /// The encoding of the tables.
+        #[arg(long, value_enum, default_value_t, value_name = "format")]
+        aet_format: AetFormat,
+    },
+
+    /// Work with profiles saved by “--profile --profile-format json”.
+    #[command(subcommand)]
+    Profile(ProfileCommand),
+
+    /// Step through the execution of a Triton VM program.
+    ///
+    /// Uses the same program, input, and non-determinism as command “run”. Reads
+    /// debugging commands from stdin, one per line, which makes it possible to
+    /// script debugging sessions. Enter “help” for a list of available commands.
+    ///
+    /// In contrast to the other commands, an initial state is used as is. This
+    /// allows inspecting a state dumped because of a crash.
+    ///
+    /// If Triton VM has crashed by the end of the session, the command fails
+    /// like command “run” would.
+    Debug(RunArgs),
+
+    /// Summarize a claim and, if it exists, the corresponding proof.
+    ///
+    /// Prints the program digest, the public input and output, and, if the proof
+    /// file exists, the size of the proof, the padded height, the length of the
+    /// FRI domain, and the STARK parameters. Does not verify the proof.
+    Inspect(ProofArtifacts),
+
+    /// Print the digest of a Triton VM program.
+    ///
+    /// The digest is the same as the program digest in any claim about the
+    /// program.
+    Digest {
+        /// A file containing a list of Triton instructions.
+        #[arg(long, value_name = "file")]
+        program: String,
+    },
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, clap::Args)]
+#[derive(Debug, Clone, Eq, PartialEq, clap::Subcommand)]
+pub enum ProfileCommand {
+    /// Compare two profiles of the same kind.
+    ///
+    /// Both profiles are either execution profiles, as saved by command “run”, or
+    /// performance profiles, as saved by commands “prove” and “verify”. A json
+    /// report that includes a profile works, too. Prints how the totals, like
+    /// the cycle count or the duration, changed, as well as the changes for every
+    /// subroutine or every step of the prover or verifier.
+    ///
+    /// If some total increased by more than its threshold, the command exits with
+    /// a dedicated exit code. This allows blocking changes that make a program
+    /// more expensive to prove. The thresholds apply only to the totals, not to
+    /// individual subroutines or steps.
+    Diff(ProfileDiffArgs),
+}
+
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash, clap::Args)]
+#[non_exhaustive]
+pub struct ProfileDiffArgs {
+    /// The profile to compare against.
+    #[arg(value_name = "old profile")]
+    pub old: String,
+
+    /// The profile to compare.
+    #[arg(value_name = "new profile")]
+    pub new: String,
+
+    /// The largest acceptable increase of the cycle count, in percent.
+    #[arg(long, value_name = "percent")]
+    pub max_cycle_count_increase: Option<u64>,
+
+    /// The largest acceptable increase of the padded height, in percent.
+    ///
+    /// Since the padded height is a power of two, any increase at least doubles
+    /// it. Use 0 to reject every increase.
+    #[arg(long, value_name = "percent")]
+    pub max_padded_height_increase: Option<u64>,
+
+    /// The largest acceptable increase of the height of any table, in percent.
+    ///
+    /// Both profiles must know the height of every table.
+    #[arg(long, value_name = "percent")]
+    pub max_table_height_increase: Option<u64>,
+
+    /// The largest acceptable increase of the duration of proving or
+    /// verification, in percent.
+    #[arg(long, value_name = "percent")]
+    pub max_duration_increase: Option<u64>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, clap::Args)]
+#[non_exhaustive]
+pub struct BenchArgs {
+    /// The log₂ of the smallest padded height to prove. At least 16.
+    #[arg(
+        long,
+        value_name = "log₂ height",
+        default_value_t = bench::MIN_LOG2_PADDED_HEIGHT,
+        value_parser = log2_padded_height_parser(),
+    )]
+    pub min_log2_padded_height: u32,
+
+    /// The log₂ of the largest padded height to prove. At most 31.
+    #[arg(
+        long,
+        value_name = "log₂ height",
+        default_value_t = bench::MAX_LOG2_PADDED_HEIGHT,
+        value_parser = log2_padded_height_parser(),
+    )]
+    pub max_log2_padded_height: u32,
+
+    /// The memory the prover may use, in MiB. By default, all the memory that
+    /// is available right before proving.
+    #[arg(long, value_name = "MiB")]
+    pub max_memory: Option<u64>,
+
+    #[command(flatten)]
+    pub stark: StarkArgs,
+}
+
+#[derive(Debug, Default, Clone, Eq, PartialEq, clap::Args)]
+#[non_exhaustive]
 pub struct Flags {
     /// Print command-dependent profiling information.
     #[arg(long, default_value_t = false)]
     pub profile: bool,
+
+    #[command(flatten)]
+    pub profile_args: ProfileArgs,
+
+    /// Write the profile to this file instead of stdout.
+    ///
+    /// With “--format json”, the report still includes the profile.
+    #[arg(long, value_name = "file", requires = "profile")]
+    pub profile_out: Option<String>,
+
+    /// The format of everything printed to stdout.
+    ///
+    /// With “json”, every command prints exactly one json document, even if the
+    /// command fails. This includes the profile, if requested, and problems
+    /// with the arguments themselves. For those, the command is absent if it
+    /// cannot be told.
+    #[arg(long, value_enum, default_value_t = Format::Human)]
+    pub format: Format,
+
+    /// When to use colors in human-readable error messages.
+    ///
+    /// With “auto”, colors are used if stderr is a terminal and the environment
+    /// variable NO_COLOR is not set.
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, clap::ValueEnum)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, clap::ValueEnum)]
+pub enum Format {
+    /// Human-readable output.
+    #[default]
+    Human,
+
+    /// Machine-readable output: a single json document.
+    Json,
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, clap::Args)]
+#[non_exhaustive]
+pub struct ProfileArgs {
+    /// The format of the profile.
+    ///
+    /// With “json”, the profile is a json document, independent of the format
+    /// of everything else. Only execution profiles, that is, those of command
+    /// “run”, can be rendered as folded stacks or as a flamegraph.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t,
+        value_name = "format",
+        requires = "profile"
+    )]
+    pub profile_format: ProfileFormat,
+
+    /// The table whose height weighs the subroutines in folded stacks and
+    /// flamegraphs. The height of the processor table is the cycle count.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t,
+        value_name = "table",
+        requires = "profile"
+    )]
+    pub profile_table: ProfileTable,
+}
+
+/// The encoding of a profile.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, clap::ValueEnum)]
+pub enum ProfileFormat {
+    /// The profile as rendered by Triton VM.
+    #[default]
+    Human,
+
+    /// A json document.
+    Json,
+
+    /// One line per call stack, followed by its weight, as consumed by common
+    /// flamegraph tools.
+    Folded,
+
+    /// An SVG image of a flamegraph.
+    Flamegraph,
+}
+
+/// One of Triton VM's tables.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, clap::ValueEnum)]
+pub enum ProfileTable {
+    Program,
+    #[default]
+    Processor,
+    OpStack,
+    Ram,
+    JumpStack,
+    Hash,
+    Cascade,
+    Lookup,
+    U32,
+}
+
+/// The encoding of public input.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, clap::ValueEnum)]
+pub enum InputFormat {
+    /// Base field elements in decimal, separated by commas or whitespace.
+    /// Negative numbers are counted down from the field's modulus.
+    #[default]
+    Decimal,
+
+    /// Base field elements in hexadecimal with optional prefix “0x”, separated
+    /// by commas or whitespace.
+    Hex,
+
+    /// Unsigned 32-bit integers in decimal, separated by commas or whitespace.
+    /// Anything outside the range of u32s is rejected.
+    U32,
+
+    /// A json array of base field elements, given as numbers or as strings of
+    /// decimal numbers.
+    Json,
+
+    /// Extension field elements as coefficient triples, like “(1, 2, 3)”. After
+    /// instruction `read_io 3`, coefficient 0 is on top of the stack.
+    Xfe,
+
+    /// Digests in hexadecimal, like printed by command “digest”. After
+    /// instruction `read_io 5`, element 0 of the digest is on top of the stack.
+    Digest,
+}
+
+/// The encoding of public output.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Base field elements in decimal, separated by commas.
+    #[default]
+    Decimal,
+
+    /// Base field elements in decimal, one per line.
+    Lines,
+
+    /// Base field elements in hexadecimal with prefix “0x”, one per line.
+    Hex,
+
+    /// A json array of base field elements in their canonical representation.
+    Json,
+
+    /// Extension field elements as coefficient triples, like “(1, 2, 3)”, one
+    /// per line. Coefficient 0 is the first element written by instruction
+    /// `write_io 3`.
+    Xfe,
+
+    /// Digests in hexadecimal, like printed by command “digest”, one per line.
+    /// Element 0 of a digest is the first element written by instruction
+    /// `write_io 5`.
+    Digest,
+}
+
+/// The encoding of an execution trace.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, clap::ValueEnum)]
+pub enum TraceFormat {
+    /// Comma-separated values with a header row.
+    #[default]
+    Csv,
+
+    /// One json object per line.
+    Jsonl,
+}
+
+/// The encoding of the tables of an algebraic execution trace.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, clap::ValueEnum)]
+pub enum AetFormat {
+    /// Comma-separated values with a header row of column names.
+    #[default]
+    Csv,
+
+    /// The elements in canonical representation as 8 little-endian bytes each,
+    /// row after row, without any header.
+    Binary,
 }
 
 /// The arguments required for executing a Triton VM program.
@@ -73,109 +510,555 @@ pub struct Flags {
 // Relevant issues:
 // - <https://github.com/clap-rs/clap/issues/2621>
 // - <https://github.com/clap-rs/clap/pull/5700>
-#[derive(Debug, Clone, Eq, PartialEq, clap::Args)]
+#[derive(Debug, Default, Clone, Eq, PartialEq, clap::Args)]
+#[non_exhaustive]
 pub struct RunArgs {
     /// The entire initial state, json-encoded. Easiest to obtain programmatically.
     ///
     /// Note that the initial state is not used as is. Instead, the program, public
     /// input, and non-determinism are extracted, then used as if they were passed
     /// separately. Any custom instruction pointer, Sponge state, etc. are
-    /// discarded, as generating a valid proof would be impossible otherwise.
+    /// discarded, as generating a valid proof would be impossible otherwise. For
+    /// a state dumped because of a crash, the recorded initial input is used. The
+    /// only exception is command “debug”, which resumes execution from the given
+    /// state.
+    ///
+    /// Use “-” to read from stdin. Only one file can be read from stdin.
     ///
-    /// Conflicts with “program”, “input”, “input file”, and “non-determinism”.
+    /// Conflicts with “program”, “input”, “input file”, “input format”,
+    /// “non-determinism”, “secret input”, “secret digests”, and “ram”.
     #[arg(
         long,
         conflicts_with = "program",
         conflicts_with = "input",
         conflicts_with = "input_file",
+        conflicts_with = "input_format",
         conflicts_with = "non_determinism",
+        conflicts_with = "secret_input",
+        conflicts_with = "secret_digests",
+        conflicts_with = "ram",
         value_name = "json file"
     )]
     pub initial_state: Option<String>,
 
     #[command(flatten)]
     pub separate_files: SeparateFilesRunArgs,
+
+    /// If Triton VM crashes, write its state at the failing instruction to this
+    /// file, json-encoded, together with the input execution started with. The
+    /// file can be used as the “initial state”: command “debug” resumes at the
+    /// failing instruction, all other commands replay execution from the start.
+    #[arg(long, value_name = "json file")]
+    pub dump_state_on_error: Option<String>,
+
+    #[command(flatten)]
+    pub limits: Limits,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, clap::Args)]
+/// How to present the public output of a program.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash, clap::Args)]
+#[non_exhaustive]
+pub struct OutputArgs {
+    /// The encoding of the public output.
+    #[arg(long, value_enum, default_value_t, value_name = "format")]
+    pub output_format: OutputFormat,
+
+    /// Write the public output to this file instead of stdout.
+    #[arg(long, value_name = "file")]
+    pub output_file: Option<String>,
+}
+
+/// Where and how to write an execution trace.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash, clap::Args)]
+#[non_exhaustive]
+pub struct TraceArgs {
+    /// The encoding of the trace.
+    #[arg(long, value_enum, default_value_t, value_name = "format")]
+    pub trace_format: TraceFormat,
+
+    /// Write the trace to this file instead of stdout.
+    #[arg(long, value_name = "file")]
+    pub trace_file: Option<String>,
+}
+
+/// Bounds on the resources a program may consume. Exceeding any of them aborts
+/// execution.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, clap::Args)]
+#[non_exhaustive]
+pub struct Limits {
+    /// Abort execution if the program does not terminate within this many cycles.
+    #[arg(long, value_name = "cycles")]
+    pub max_cycles: Option<u32>,
+
+    /// Abort if the padded height of the execution trace exceeds this value. In
+    /// particular, command “prove” refuses to start proving.
+    ///
+    /// The padded height is a power of two and the main driver of proving cost.
+    #[arg(long, value_name = "height")]
+    pub max_padded_height: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, Eq, PartialEq, clap::Args)]
+#[non_exhaustive]
 pub struct SeparateFilesRunArgs {
-    /// A file containing a list of Triton instructions.
+    /// A file containing a list of Triton instructions. Use “-” to read from stdin.
     #[arg(long, value_name = "file")]
     pub program: Option<String>,
 
     #[command(flatten)]
     pub public_input: Option<InputArgs>,
 
-    /// A file containing the entire non-determinism, json-encoded.
+    /// A file containing the entire non-determinism, json-encoded. Use “-” to
+    /// read from stdin.
+    ///
+    /// The secret input, secret digests, and RAM given in separate files are
+    /// added to it.
     #[arg(long)]
     pub non_determinism: Option<String>,
+
+    /// A file containing the secret input, the elements of the non-determinism
+    /// the program reads with instruction `divine`. The elements are encoded
+    /// like the public input; see “input format”.
+    #[arg(long, value_name = "file")]
+    pub secret_input: Option<String>,
+
+    /// A file containing the secret digests, the digests the program reads
+    /// with instruction `merkle_step`. The digests are in hexadecimal, like
+    /// printed by command “digest”, and separated by whitespace or commas.
+    #[arg(long, value_name = "file")]
+    pub secret_digests: Option<String>,
+
+    /// A file containing the initial content of RAM, one “address: value” pair
+    /// per line. An address must not also be set by “non-determinism”.
+    #[arg(long, value_name = "file")]
+    pub ram: Option<String>,
 }
 
 // Another “fake enum” – see `RunArgs` for a more detailed explanation.
-#[derive(Debug, Clone, Eq, PartialEq, clap::Args)]
+#[derive(Debug, Default, Clone, Eq, PartialEq, clap::Args)]
+#[non_exhaustive]
 pub struct InputArgs {
-    /// A comma-separated list of the base field elements the program can use as its
-    /// public input.
+    /// A list of the elements the program can use as its public input. By default,
+    /// comma-separated base field elements; see “input format”.
     ///
     /// Conflicts with “input file”.
-    #[arg(long, conflicts_with = "input_file")]
+    #[arg(long, conflicts_with = "input_file", allow_hyphen_values = true)]
     pub input: Option<String>,
 
-    /// A file containing a comma-separated list of base field elements the program
-    /// can use as its input.
+    /// A file containing a list of the elements the program can use as its public
+    /// input, in the same format as “input”. Use “-” to read from stdin.
     ///
     /// Conflicts with “input”.
     #[arg(long, value_name = "file")]
     pub input_file: Option<String>,
+
+    /// The encoding of the public input and the secret input.
+    #[arg(long, value_enum, default_value_t, value_name = "format")]
+    pub input_format: InputFormat,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, clap::Args)]
+#[non_exhaustive]
 pub struct ProofArtifacts {
-    /// The file of the claim that is to be proven or verified.
-    #[arg(long, value_name = "file", default_value_t = String::from("triton.claim"))]
-    pub claim: String,
+    /// The file of the claim that is to be proven or verified. By default,
+    /// “triton.claim”.
+    ///
+    /// The claim is also contained in the proof file. When verifying, the claim
+    /// file is only read if it is given explicitly, in which case it takes
+    /// precedence. Proofs in the legacy format always require the claim file.
+    ///
+    /// Use “-” to read from stdin or write to stdout.
+    #[arg(long, value_name = "file")]
+    pub claim: Option<String>,
 
     /// The file of the proof for the claim that is to be proven or verified.
+    ///
+    /// Contains a header identifying the file format and the proof version of
+    /// the Triton VM that produced the proof, the STARK parameters, the claim,
+    /// and the proof. Proofs in the legacy format, which lack all but the proof,
+    /// can be verified, too.
+    ///
+    /// Use “-” to read from stdin or write to stdout.
     #[arg(long, value_name = "file", default_value_t = String::from("triton.proof"))]
     pub proof: String,
+
+    /// The file of the STARK parameters, json-encoded. Only used for verifying
+    /// proofs in the legacy format.
+    ///
+    /// If the file does not exist, the default parameters are used.
+    #[arg(long, value_name = "file", default_value_t = String::from("triton.parameters"))]
+    pub parameters: String,
+}
+
+/// What the verifier expects the claim to be about, in addition to the claim
+/// being true.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash, clap::Args)]
+#[non_exhaustive]
+pub struct Expectations {
+    /// A file containing a list of Triton instructions. Fail unless the claim is
+    /// about this program.
+    #[arg(long, value_name = "file")]
+    pub expect_program: Option<String>,
+
+    /// A list of base field elements, by default comma-separated; see “input
+    /// format”. Fail unless the claim's public input is exactly this.
+    ///
+    /// Conflicts with “expect input file”.
+    #[arg(
+        long,
+        conflicts_with = "expect_input_file",
+        allow_hyphen_values = true,
+        value_name = "input"
+    )]
+    pub expect_input: Option<String>,
+
+    /// A file containing a list of base field elements, in the same format as
+    /// “expect input”. Fail unless the claim's public input is exactly this.
+    ///
+    /// Conflicts with “expect input”.
+    #[arg(long, value_name = "file")]
+    pub expect_input_file: Option<String>,
+
+    /// A list of base field elements, by default comma-separated; see “input
+    /// format”. Fail unless the claim's public output is exactly this.
+    ///
+    /// Conflicts with “expect output file”.
+    #[arg(
+        long,
+        conflicts_with = "expect_output_file",
+        allow_hyphen_values = true,
+        value_name = "output"
+    )]
+    pub expect_output: Option<String>,
+
+    /// A file containing a list of base field elements, in the same format as
+    /// “expect output”. Fail unless the claim's public output is exactly this.
+    ///
+    /// Conflicts with “expect output”.
+    #[arg(long, value_name = "file")]
+    pub expect_output_file: Option<String>,
+
+    /// The encoding of the expected public input and output.
+    #[arg(long, value_enum, default_value_t, value_name = "format")]
+    pub input_format: InputFormat,
+
+    /// The smallest acceptable security level in bits. Fail if the STARK
+    /// parameters the proof comes with provide less. By default, the security
+    /// level of Triton VM's default parameters.
+    ///
+    /// The security level is derived from the number of collinearity checks and
+    /// the FRI expansion factor. The security level recorded alongside them is
+    /// not trusted.
+    #[arg(long, value_name = "bits")]
+    pub min_security_level: Option<usize>,
+}
+
+/// The parsed [`Expectations`]. Everything that is `None` is not checked.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct ExpectedClaim {
+    pub program_digest: Option<Digest>,
+    pub input: Option<Vec<BFieldElement>>,
+    pub output: Option<Vec<BFieldElement>>,
+    pub min_security_level: Option<usize>,
+}
+
+/// The parameters of the STARK used for proving. Unless specified otherwise,
+/// the default parameters are used.
+///
+/// The parameters are recorded alongside the proof, which allows the verifier
+/// to use the very same parameters.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash, clap::Args)]
+#[non_exhaustive]
+pub struct StarkArgs {
+    /// The conjectured security level in bits.
+    ///
+    /// Conflicts with “number of collinearity checks” and “stark parameters”.
+    #[arg(
+        long,
+        value_name = "bits",
+        conflicts_with = "num_collinearity_checks",
+        conflicts_with = "stark_parameters"
+    )]
+    pub security_level: Option<usize>,
+
+    /// The ratio between the lengths of the FRI domain and the randomized trace
+    /// domain. Must be a power of 2 greater than 1.
+    ///
+    /// Conflicts with “stark parameters”.
+    #[arg(long, value_name = "factor", conflicts_with = "stark_parameters")]
+    pub fri_expansion_factor: Option<usize>,
+
+    /// The number of collinearity checks to perform in FRI. By default, derived
+    /// from the security level and the FRI expansion factor. If given, the
+    /// security level is derived from it instead: it is the number of
+    /// collinearity checks times log₂ of the FRI expansion factor.
+    ///
+    /// Conflicts with “security level” and “stark parameters”.
+    #[arg(long, value_name = "number", conflicts_with = "stark_parameters")]
+    pub num_collinearity_checks: Option<usize>,
+
+    /// A file containing all STARK parameters, json-encoded. A file previously
+    /// written by command “prove” can be used.
+    #[arg(long, value_name = "json file")]
+    pub stark_parameters: Option<String>,
+}
+
+impl ColorChoice {
+    /// Whether to use colors when writing to stderr.
+    pub fn use_color_on_stderr(self) -> bool {
+        match self {
+            Self::Auto => std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
 }
 
+impl Command {
+    /// The name of the command, as used in reports. Verifying a batch is
+    /// distinguished from verifying a single proof.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Run { .. } => "run",
+            Self::Prove { .. } => "prove",
+            Self::Verify { batch: Some(_), .. } => "verify_batch",
+            Self::Verify { .. } => "verify",
+            Self::Estimate { .. } => "estimate",
+            Self::Bench(_) => "bench",
+            Self::Trace { .. } => "trace",
+            Self::Aet { .. } => "aet",
+            Self::Profile(ProfileCommand::Diff(_)) => "profile_diff",
+            Self::Debug(_) => "debug",
+            Self::Inspect(_) => "inspect",
+            Self::Digest { .. } => "digest",
+        }
+    }
+}
+
+/// Some argument is invalid, for example, because it contradicts another
+/// argument. Problems detected by the argument parser itself are reported by
+/// the parser.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InvalidArgument(pub String);
+
+impl Display for InvalidArgument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidArgument {}
+
+/// The reason a program could not be parsed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ProgramParseError {
+    /// The file containing the program.
+    pub file: String,
+    pub diagnostics: Vec<Diagnostic>,
+
+    /// The parser's own description of the problem. Rendered only if there are
+    /// no diagnostics.
+    pub message: String,
+}
+
+impl ProgramParseError {
+    /// Render all diagnostics. If `color` is set, ANSI escape codes are used.
+    pub fn render(&self, color: bool) -> String {
+        if self.diagnostics.is_empty() {
+            return format!("error: could not parse “{}”:\n{}", self.file, self.message);
+        }
+
+        self.diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.render(&self.file, color))
+            .join("\n\n")
+    }
+}
+
+impl Display for ProgramParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render(false))
+    }
+}
+
+impl std::error::Error for ProgramParseError {}
+
+/// Some [limit](Limits) was exceeded.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum LimitExceeded {
+    /// The program did not terminate within the given number of cycles.
+    Cycles(u32),
+
+    /// The padded height of the execution trace exceeds the given height.
+    PaddedHeight(usize),
+}
+
+impl Display for LimitExceeded {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cycles(max) => write!(f, "program did not terminate within {max} cycles"),
+            Self::PaddedHeight(max) => write!(f, "padded height exceeds the limit of {max}"),
+        }
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+/// The claim is not about what the verifier [expects](Expectations).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum ClaimMismatch {
+    ProgramDigest {
+        expected: Digest,
+        actual: Digest,
+    },
+    Input {
+        expected: Vec<BFieldElement>,
+        actual: Vec<BFieldElement>,
+    },
+    Output {
+        expected: Vec<BFieldElement>,
+        actual: Vec<BFieldElement>,
+    },
+}
+
+impl Display for ClaimMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ProgramDigest { expected, actual } => write!(
+                f,
+                "the claim is about program {actual:x}, but program {expected:x} was expected"
+            ),
+            Self::Input { expected, actual } => write!(
+                f,
+                "the claim's public input is [{}], but [{}] was expected",
+                actual.iter().join(", "),
+                expected.iter().join(", "),
+            ),
+            Self::Output { expected, actual } => write!(
+                f,
+                "the claim's public output is [{}], but [{}] was expected",
+                actual.iter().join(", "),
+                expected.iter().join(", "),
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ClaimMismatch {}
+
+/// The STARK parameters of a proof provide less security than the verifier
+/// [expects](Expectations::min_security_level).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct InsufficientSecurity {
+    pub required: usize,
+    pub actual: usize,
+}
+
+impl Display for InsufficientSecurity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Self { required, actual } = self;
+        write!(
+            f,
+            "the STARK parameters provide a security level of {actual} bits, \
+             but at least {required} bits are required"
+        )
+    }
+}
+
+impl std::error::Error for InsufficientSecurity {}
+
 impl RunArgs {
     pub fn parse(self) -> Result<(Program, PublicInput, NonDeterminism)> {
+        ensure_stdin_is_read_at_most_once(self.paths())?;
         if let Some(initial_state) = self.initial_state {
-            let file = fs::File::open(initial_state)?;
-            let reader = std::io::BufReader::new(file);
-            let state: VMState = serde_json::from_reader(reader)?;
-            let input = PublicInput::new(state.public_input.into());
-            let non_determinism = NonDeterminism::new(state.secret_individual_tokens)
-                .with_digests(state.secret_digests)
-                .with_ram(state.ram);
-            return Ok((state.program, input, non_determinism));
+            let (state, initial_input) = read_state(initial_state)?;
+            let input = PublicInput::new(initial_input.public_input);
+            return Ok((state.program, input, initial_input.non_determinism));
         }
 
         let SeparateFilesRunArgs {
             program,
             public_input,
             non_determinism,
+            secret_input,
+            secret_digests,
+            ram,
         } = self.separate_files;
         let Some(program) = program else {
-            bail!("error: either argument “initial state” or ”program“ must be supplied");
+            bail!(InvalidArgument(
+                "either argument “initial state” or ”program“ must be supplied".into()
+            ));
         };
 
-        let program = Self::parse_program(program)?;
+        let input_format = public_input
+            .as_ref()
+            .map(|input| input.input_format)
+            .unwrap_or_default();
+        let program = read_program(program)?;
         let public_input = Self::parse_public_input(public_input)?;
-        let non_determinism = Self::parse_non_determinism(non_determinism)?;
+        let mut non_determinism = Self::parse_non_determinism(non_determinism)?;
+        if let Some(secret_input) = secret_input {
+            let tokens = input::parse(&read_string(&secret_input)?, input_format)?;
+            non_determinism.individual_tokens.extend(tokens);
+        }
+        if let Some(secret_digests) = secret_digests {
+            let digests = input::parse_digests(&read_string(&secret_digests)?)?;
+            non_determinism.digests.extend(digests);
+        }
+        if let Some(ram) = ram {
+            for (address, value) in input::parse_ram(&read_string(&ram)?)? {
+                if non_determinism.ram.insert(address, value).is_some() {
+                    let message =
+                        format!("RAM address {address} is set by both “non-determinism” and “ram”");
+                    bail!(InvalidArgument(message));
+                }
+            }
+        }
 
         Ok((program, public_input, non_determinism))
     }
 
-    fn parse_program(path: String) -> Result<Program> {
-        let code = fs::read_to_string(path)?;
+    /// Like [`parse`](Self::parse), but produces a [`VMState`]. In contrast to
+    /// [`parse`](Self::parse), an initial state is used as is, including its
+    /// instruction pointer, stacks, etc.
+    pub fn parse_state(self) -> Result<VMState> {
+        let (state, _) = self.parse_state_with_initial_input()?;
+        Ok(state)
+    }
+
+    /// Like [`parse_state`](Self::parse_state), additionally returning the
+    /// input execution started with. For a dumped state, this is the recorded
+    /// input, if any.
+    pub fn parse_state_with_initial_input(self) -> Result<(VMState, InitialInput)> {
+        ensure_stdin_is_read_at_most_once(self.paths())?;
+        if let Some(initial_state) = self.initial_state {
+            return read_state(initial_state);
+        }
+
+        let (program, public_input, non_determinism) = self.parse()?;
+        let initial_input = InitialInput::new(&public_input, &non_determinism);
+        let state = VMState::new(program, public_input, non_determinism);
+        Ok((state, initial_input))
+    }
 
-        // own the error to work around lifetime issues
-        let program = Program::from_code(&code).map_err(|err| anyhow!("{err}"))?;
+    /// Whether any of the arguments is to be read from stdin.
+    pub fn reads_stdin(&self) -> bool {
+        self.paths().into_iter().any(|path| path == Some(STDIO))
+    }
 
-        Ok(program)
+    /// All paths of files to read from.
+    fn paths(&self) -> Vec<Option<&str>> {
+        let input_file = self.separate_files.public_input.as_ref();
+        let input_file = input_file.and_then(|input| input.input_file.as_deref());
+        vec![
+            self.initial_state.as_deref(),
+            self.separate_files.program.as_deref(),
+            input_file,
+            self.separate_files.non_determinism.as_deref(),
+            self.separate_files.secret_input.as_deref(),
+            self.separate_files.secret_digests.as_deref(),
+            self.separate_files.ram.as_deref(),
+        ]
     }
 
     fn parse_public_input(public_input: Option<InputArgs>) -> Result<PublicInput> {
@@ -190,46 +1073,572 @@ impl RunArgs {
         let Some(path) = non_determinism else {
             return Ok(NonDeterminism::default());
         };
-        let file = fs::File::open(path)?;
-        let reader = std::io::BufReader::new(file);
 
-        Ok(serde_json::from_reader(reader)?)
+        Ok(serde_json::from_slice(&read_bytes(&path)?)?)
+    }
+}
+
+impl Limits {
+    /// Check whether executing one more cycle is within the limits.
+    ///
+    /// Since the padded height is at least the cycle count, the maximum padded
+    /// height also bounds the number of cycles.
+    pub fn check_cycle_count(&self, cycle_count: u32) -> Result<(), LimitExceeded> {
+        if let Some(max) = self.max_cycles
+            && cycle_count >= max
+        {
+            return Err(LimitExceeded::Cycles(max));
+        }
+        if let Some(max) = self.max_padded_height
+            && usize::try_from(cycle_count).is_ok_and(|cycles| cycles >= max)
+        {
+            return Err(LimitExceeded::PaddedHeight(max));
+        }
+
+        Ok(())
+    }
+
+    pub fn check_padded_height(&self, padded_height: usize) -> Result<(), LimitExceeded> {
+        match self.max_padded_height {
+            Some(max) if padded_height > max => Err(LimitExceeded::PaddedHeight(max)),
+            _ => Ok(()),
+        }
     }
 }
 
 impl InputArgs {
+    fn is_given(&self) -> bool {
+        self.input.is_some() || self.input_file.is_some()
+    }
+
     pub fn parse(self) -> Result<PublicInput> {
         let input = self
             .input_file
-            .map(fs::read_to_string)
+            .map(|path| read_string(&path))
             .transpose()?
             .or(self.input)
-            .unwrap_or_default()
-            .split(',')
-            .map(|i| i.trim().parse())
-            .collect::<Result<_, _>>()?;
+            .unwrap_or_default();
 
-        Ok(PublicInput::new(input))
+        Ok(PublicInput::new(input::parse(&input, self.input_format)?))
+    }
+}
+
+impl Expectations {
+    pub fn parse(self) -> Result<ExpectedClaim> {
+        ensure_stdin_is_read_at_most_once(self.paths())?;
+        let program_digest = self
+            .expect_program
+            .map(read_program)
+            .transpose()?
+            .map(|program| program.hash());
+
+        let input_format = self.input_format;
+        let parse_elements = |input, input_file| -> Result<_> {
+            let input_args = InputArgs {
+                input,
+                input_file,
+                input_format,
+            };
+            if !input_args.is_given() {
+                return Ok(None);
+            }
+            Ok(Some(input_args.parse()?.individual_tokens))
+        };
+        let input = parse_elements(self.expect_input, self.expect_input_file)?;
+        let output = parse_elements(self.expect_output, self.expect_output_file)?;
+
+        let min_security_level = self
+            .min_security_level
+            .unwrap_or(Stark::default().security_level);
+
+        Ok(ExpectedClaim {
+            program_digest,
+            input,
+            output,
+            min_security_level: Some(min_security_level),
+        })
+    }
+
+    /// All paths of files to read from.
+    pub fn paths(&self) -> Vec<Option<&str>> {
+        vec![
+            self.expect_program.as_deref(),
+            self.expect_input_file.as_deref(),
+            self.expect_output_file.as_deref(),
+        ]
+    }
+}
+
+impl ExpectedClaim {
+    /// Check that the claim is about what is expected.
+    pub fn check(&self, claim: &Claim) -> Result<(), ClaimMismatch> {
+        if let Some(expected) = self.program_digest
+            && expected != claim.program_digest
+        {
+            let actual = claim.program_digest;
+            return Err(ClaimMismatch::ProgramDigest { expected, actual });
+        }
+        if let Some(expected) = &self.input
+            && expected != &claim.input
+        {
+            let expected = expected.clone();
+            let actual = claim.input.clone();
+            return Err(ClaimMismatch::Input { expected, actual });
+        }
+        if let Some(expected) = &self.output
+            && expected != &claim.output
+        {
+            let expected = expected.clone();
+            let actual = claim.output.clone();
+            return Err(ClaimMismatch::Output { expected, actual });
+        }
+
+        Ok(())
+    }
+
+    /// Check that the STARK parameters provide at least the expected security
+    /// level.
+    pub fn check_security_level(&self, stark: &Stark) -> Result<(), InsufficientSecurity> {
+        let actual = conjectured_security_level(stark);
+        match self.min_security_level {
+            Some(required) if actual < required => Err(InsufficientSecurity { required, actual }),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl StarkArgs {
+    pub fn parse(self) -> Result<Stark> {
+        if let Some(path) = self.stark_parameters {
+            let file = fs::File::open(&path)?;
+            let stark = serde_json::from_reader(file)?;
+            ensure_stark_parameters_are_valid(&stark)
+                .with_context(|| format!("invalid STARK parameters in “{path}”"))?;
+            return Ok(stark);
+        }
+        if self.security_level.is_some() && self.num_collinearity_checks.is_some() {
+            bail!(InvalidArgument(
+                "the security level follows from the number of collinearity checks".into()
+            ));
+        }
+
+        let default = Stark::default();
+        let fri_expansion_factor = self
+            .fri_expansion_factor
+            .unwrap_or(default.fri_expansion_factor);
+        if fri_expansion_factor <= 1 || !fri_expansion_factor.is_power_of_two() {
+            bail!(InvalidArgument(
+                "the FRI expansion factor must be a power of 2 greater than 1".into()
+            ));
+        }
+        let log2_fri_expansion_factor = usize::try_from(fri_expansion_factor.ilog2())?;
+
+        // Deriving the number of trace randomizers is best left to Triton VM.
+        // It is derived from the number of collinearity checks, which, in turn,
+        // is derived from the security level. Working backwards gives the
+        // desired number of collinearity checks.
+        let security_level = match self.num_collinearity_checks {
+            None => self.security_level.unwrap_or(default.security_level),
+            Some(0) => bail!(InvalidArgument(
+                "the number of collinearity checks must be positive".into()
+            )),
+            Some(num_collinearity_checks) => {
+                let Some(security_level) =
+                    num_collinearity_checks.checked_mul(log2_fri_expansion_factor)
+                else {
+                    bail!(InvalidArgument("too many collinearity checks".into()));
+                };
+                security_level
+            }
+        };
+        if security_level == 0 {
+            bail!(InvalidArgument(
+                "the security level must be positive".into()
+            ));
+        }
+
+        let stark = Stark::new(security_level, log2_fri_expansion_factor);
+        ensure_stark_parameters_are_valid(&stark)?;
+
+        Ok(stark)
+    }
+}
+
+impl Default for BenchArgs {
+    /// The same defaults as on the command line.
+    fn default() -> Self {
+        Self {
+            min_log2_padded_height: bench::MIN_LOG2_PADDED_HEIGHT,
+            max_log2_padded_height: bench::MAX_LOG2_PADDED_HEIGHT,
+            max_memory: None,
+            stark: StarkArgs::default(),
+        }
+    }
+}
+
+impl Default for ProofArtifacts {
+    /// The same files as on the command line.
+    fn default() -> Self {
+        Self {
+            claim: None,
+            proof: "triton.proof".to_string(),
+            parameters: "triton.parameters".to_string(),
+        }
     }
 }
 
 impl ProofArtifacts {
-    pub fn read(&self) -> Result<(Claim, Proof)> {
-        let claim_file = fs::File::open(&self.claim)?;
-        let claim = serde_json::from_reader(claim_file)?;
+    /// The claim file used if none is given.
+    pub const DEFAULT_CLAIM: &str = "triton.claim";
+
+    /// The claim file, or the [default](Self::DEFAULT_CLAIM) if none is given.
+    pub fn claim_file(&self) -> &str {
+        self.claim.as_deref().unwrap_or(Self::DEFAULT_CLAIM)
+    }
+
+    /// Read the claim, the proof, and the STARK parameters. If the proof file is
+    /// a [proof container](ProofContainer), everything is read from it, except
+    /// that a given claim file takes precedence over the contained claim.
+    /// Otherwise, the proof file is in the legacy format and the claim and the
+    /// parameters are read from their respective files.
+    pub fn read(&self) -> Result<(Claim, Proof, Stark)> {
+        self.read_with_default_claim(Self::DEFAULT_CLAIM)
+    }
+
+    /// Like [`read`](Self::read), but proofs in the legacy format use the given
+    /// claim file if no claim file is given.
+    pub fn read_with_default_claim(&self, default_claim: &str) -> Result<(Claim, Proof, Stark)> {
+        let (claim, proof, stark, _) = self.read_artifacts(default_claim)?;
+        Ok((claim, proof, stark))
+    }
 
-        let proof_file = fs::File::open(&self.proof)?;
-        let proof = bincode::deserialize_from(proof_file)?;
+    /// Like [`read`](Self::read), additionally returning the header of the
+    /// [proof container](ProofContainer). Proofs in the legacy format have no
+    /// header.
+    pub fn read_with_header(&self) -> Result<(Claim, Proof, Stark, Option<Header>)> {
+        self.read_artifacts(Self::DEFAULT_CLAIM)
+    }
+
+    fn read_artifacts(&self, default_claim: &str) -> Result<(Claim, Proof, Stark, Option<Header>)> {
+        ensure_stdin_is_read_at_most_once(self.paths())?;
+        let proof_bytes = read_bytes(&self.proof)?;
+        if ProofContainer::is_container(&proof_bytes) {
+            let (header, container) = ProofContainer::decode_with_header(&proof_bytes)?;
+            let ProofContainer {
+                stark,
+                claim,
+                proof,
+            } = container;
+            ensure_stark_parameters_are_valid(&stark)
+                .context("invalid STARK parameters in the proof container")?;
+            let claim = match &self.claim {
+                Some(path) => serde_json::from_slice(&read_bytes(path)?)?,
+                None => claim,
+            };
+            return Ok((claim, proof, stark, Some(header)));
+        }
+
+        // without a claim next to it, the proof is most likely a corrupt container
+        let claim_bytes = match &self.claim {
+            Some(path) => read_bytes(path)?,
+            None => read_bytes_if_exists(default_claim)?.ok_or_else(|| {
+                let proof = &self.proof;
+                MalformedProof(format!(
+                    "“{proof}” is not a proof container, and there is no claim file \
+                     “{default_claim}” for a proof in the legacy format"
+                ))
+            })?,
+        };
+        let claim: Claim = serde_json::from_slice(&claim_bytes)?;
+        if claim.version != triton_vm::proof::CURRENT_VERSION {
+            bail!(UnsupportedVersion::Claim(claim.version));
+        }
+
+        let proof = bincode::deserialize(&proof_bytes)
+            .context("malformed proof – it might have been produced by another Triton VM")?;
 
-        Ok((claim, proof))
+        // proofs generated before the parameters were recorded use the defaults
+        let stark = match read_bytes_if_exists(&self.parameters)? {
+            Some(bytes) => serde_json::from_slice(&bytes)?,
+            None => Stark::default(),
+        };
+        ensure_stark_parameters_are_valid(&stark)
+            .with_context(|| format!("invalid STARK parameters in “{}”", self.parameters))?;
+
+        Ok((claim, proof, stark, None))
+    }
+
+    /// Read only the claim file, ignoring the proof file.
+    pub fn read_claim(&self) -> Result<Claim> {
+        let claim = serde_json::from_slice(&read_bytes(self.claim_file())?)?;
+
+        Ok(claim)
     }
 
-    pub fn write(&self, claim: &Claim, proof: &Proof) -> Result<()> {
-        let claim_file = fs::File::create(&self.claim)?;
-        serde_json::to_writer(claim_file, claim)?;
+    /// Write the [proof container](ProofContainer) to the proof file. For
+    /// convenience, the claim is also written to the claim file, json-encoded.
+    /// Either, but not both, can be written to stdout.
+    pub fn write(&self, claim: &Claim, proof: &Proof, stark: &Stark) -> Result<()> {
+        self.ensure_stdout_is_written_at_most_once()?;
+        write_bytes(self.claim_file(), &serde_json::to_vec(claim)?)?;
 
-        let proof_file = fs::File::create(&self.proof)?;
-        bincode::serialize_into(proof_file, proof)?;
+        let container = ProofContainer {
+            stark: *stark,
+            claim: claim.clone(),
+            proof: proof.clone(),
+        };
+        write_bytes(&self.proof, &container.encode()?)?;
+
+        Ok(())
+    }
+
+    /// Whether the claim or the proof is to be written to stdout.
+    pub fn writes_stdout(&self) -> bool {
+        self.claim_file() == STDIO || self.proof == STDIO
+    }
+
+    pub fn ensure_stdout_is_written_at_most_once(&self) -> Result<()> {
+        if self.claim_file() == STDIO && self.proof == STDIO {
+            bail!(InvalidArgument(
+                "only one of “claim” and “proof” can be written to stdout".into()
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// All paths of files to read from.
+    pub fn paths(&self) -> Vec<Option<&str>> {
+        vec![
+            self.claim.as_deref(),
+            Some(self.proof.as_str()),
+            Some(self.parameters.as_str()),
+        ]
+    }
+}
+
+/// The security level the STARK parameters provide, in bits. In contrast to
+/// the recorded [security level](Stark::security_level), which the verifier
+/// does not use, it is derived from the number of collinearity checks and the
+/// FRI expansion factor.
+pub fn conjectured_security_level(stark: &Stark) -> usize {
+    let log2_fri_expansion_factor = stark.fri_expansion_factor.checked_ilog2().unwrap_or(0);
+    let log2_fri_expansion_factor = usize::try_from(log2_fri_expansion_factor).unwrap_or(0);
+    stark
+        .num_collinearity_checks
+        .saturating_mul(log2_fri_expansion_factor)
+}
+
+/// Fail unless Triton VM can use the STARK parameters. Parameters that were
+/// deserialized, for example from a proof file, are not checked otherwise.
+pub fn ensure_stark_parameters_are_valid(stark: &Stark) -> Result<()> {
+    if stark.security_level == 0 {
+        bail!(InvalidArgument(
+            "the security level must be positive".into()
+        ));
+    }
+    if stark.fri_expansion_factor <= 1 || !stark.fri_expansion_factor.is_power_of_two() {
+        bail!(InvalidArgument(
+            "the FRI expansion factor must be a power of 2 greater than 1".into()
+        ));
+    }
+    if stark.num_collinearity_checks == 0 {
+        bail!(InvalidArgument(
+            "the number of collinearity checks must be positive".into()
+        ));
+    }
+
+    // The verifier accepts padded heights up to 2^31. The length of the FRI
+    // domain for those must be computable.
+    let max_padded_height = 1_usize << 31;
+    let fri_domain_len = max_padded_height
+        .checked_add(stark.num_trace_randomizers)
+        .and_then(usize::checked_next_power_of_two)
+        .and_then(|len| len.checked_mul(stark.fri_expansion_factor));
+    if fri_domain_len.is_none() {
+        bail!(InvalidArgument(
+            "the FRI expansion factor or the number of trace randomizers is too large".into()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Parses the log₂ of a padded height that the
+/// [benchmark's program](bench::program) supports.
+fn log2_padded_height_parser() -> clap::builder::RangedI64ValueParser<u32> {
+    let min = i64::from(bench::MIN_LOG2_PADDED_HEIGHT);
+    let max = i64::from(bench::MAX_LOG2_PADDED_HEIGHT);
+    clap::value_parser!(u32).range(min..=max)
+}
+
+pub fn read_program(path: String) -> Result<Program> {
+    let code = read_string(&path)?;
+    let file = if path == STDIO {
+        String::from("<stdin>")
+    } else {
+        path
+    };
+
+    // own the error to work around lifetime issues
+    let program = Program::from_code(&code).map_err(|err| ProgramParseError {
+        diagnostics: Diagnostic::from_parse_error(&code, &err),
+        message: err.to_string(),
+        file,
+    })?;
+
+    Ok(program)
+}
+
+/// Read a state, and the input execution started with. If the state was not
+/// [dumped](StateDump) together with that input, the input remaining in the
+/// state is used instead.
+fn read_state(path: String) -> Result<(VMState, InitialInput)> {
+    #[derive(Deserialize)]
+    struct RecordedInput {
+        initial_input: Option<InitialInput>,
+    }
+
+    let bytes = read_bytes(&path)?;
+    let state: VMState = serde_json::from_slice(&bytes)?;
+    let RecordedInput { initial_input } = serde_json::from_slice(&bytes)?;
+    let initial_input = initial_input.unwrap_or_else(|| InitialInput::remaining_in(&state));
+
+    Ok((state, initial_input))
+}
+
+/// Read the entire file, or all of stdin if the path is [`STDIO`].
+pub fn read_bytes(path: &str) -> Result<Vec<u8>> {
+    if path == STDIO {
+        let mut bytes = Vec::new();
+        std::io::stdin().read_to_end(&mut bytes)?;
+        return Ok(bytes);
+    }
+
+    Ok(fs::read(path)?)
+}
+
+/// Like [`read_bytes`], but `None` if the file does not exist.
+fn read_bytes_if_exists(path: &str) -> Result<Option<Vec<u8>>> {
+    match read_bytes(path) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(err)
+            if err
+                .downcast_ref::<std::io::Error>()
+                .is_some_and(|err| err.kind() == std::io::ErrorKind::NotFound) =>
+        {
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Read the entire file, or all of stdin if the path is [`STDIO`].
+pub fn read_string(path: &str) -> Result<String> {
+    if path == STDIO {
+        return Ok(std::io::read_to_string(std::io::stdin())?);
+    }
+
+    Ok(fs::read_to_string(path)?)
+}
+
+/// Write to the file, or to stdout if the path is [`STDIO`].
+pub fn write_bytes(path: &str, bytes: &[u8]) -> Result<()> {
+    if path == STDIO {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(bytes)?;
+        stdout.flush()?;
+        return Ok(());
+    }
+
+    Ok(fs::write(path, bytes)?)
+}
+
+/// Create the file for writing, or write to stdout if the path is [`STDIO`].
+/// Writes are buffered.
+pub fn create(path: &str) -> Result<Box<dyn Write>> {
+    if path == STDIO {
+        return Ok(Box::new(std::io::BufWriter::new(std::io::stdout().lock())));
+    }
+
+    Ok(Box::new(std::io::BufWriter::new(fs::File::create(path)?)))
+}
+
+/// Stdin can only be read once. Fail if more than one of the given paths
+/// refers to it.
+pub fn ensure_stdin_is_read_at_most_once<'a>(
+    paths: impl IntoIterator<Item = Option<&'a str>>,
+) -> Result<()> {
+    let num_reads = paths
+        .into_iter()
+        .filter(|&path| path == Some(STDIO))
+        .count();
+    if num_reads > 1 {
+        bail!(InvalidArgument(
+            "at most one file can be read from stdin".into()
+        ));
+    }
+
+    Ok(())
+}
+
+/// The input execution started with. Recorded alongside a dumped state, such
+/// that execution can be replayed from the start.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct InitialInput {
+    pub public_input: Vec<BFieldElement>,
+    pub non_determinism: NonDeterminism,
+}
+
+impl InitialInput {
+    pub fn new(public_input: &PublicInput, non_determinism: &NonDeterminism) -> Self {
+        Self {
+            public_input: public_input.individual_tokens.clone(),
+            non_determinism: non_determinism.clone(),
+        }
+    }
+
+    /// The input not yet consumed in the given state.
+    fn remaining_in(state: &VMState) -> Self {
+        let non_determinism = NonDeterminism::new(state.secret_individual_tokens.clone())
+            .with_digests(Vec::from(state.secret_digests.clone()))
+            .with_ram(state.ram.clone());
+
+        Self {
+            public_input: state.public_input.clone().into(),
+            non_determinism,
+        }
+    }
+}
+
+/// Where to write the state of Triton VM if it crashes, and the input execution
+/// started with.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct StateDump {
+    pub path: String,
+    pub initial_input: InitialInput,
+}
+
+impl StateDump {
+    /// Write the given state to the file, json-encoded, together with the
+    /// initial input. The file can be used as the
+    /// [initial state](RunArgs::initial_state).
+    pub fn write(&self, state: &VMState) -> Result<()> {
+        #[derive(Serialize)]
+        struct Dump<'a> {
+            #[serde(flatten)]
+            state: &'a VMState,
+            initial_input: &'a InitialInput,
+        }
+
+        let dump = Dump {
+            state,
+            initial_input: &self.initial_input,
+        };
+        let file = fs::File::create(&self.path)?;
+        let mut writer = std::io::BufWriter::new(file);
+        serde_json::to_writer(&mut writer, &dump)?;
+        writer.flush()?;
 
         Ok(())
     }
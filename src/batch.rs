@@ -0,0 +1,107 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use anyhow::bail;
+use fs_err as fs;
+use rayon::prelude::*;
+use serde::Deserialize;
+
+use crate::args::ExpectedClaim;
+use crate::args::InvalidArgument;
+use crate::args::ProofArtifacts;
+
+/// An entry of a batch manifest. Unspecified files are derived from the proof
+/// file, like for proofs in a directory.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestEntry {
+    proof: PathBuf,
+    claim: Option<PathBuf>,
+    parameters: Option<PathBuf>,
+}
+
+/// Collect the [artifacts](ProofArtifacts) of all (Claim, Proof)-pairs listed
+/// in a manifest or found in a directory.
+pub fn collect(path: &str) -> Result<Vec<ProofArtifacts>> {
+    let path = Path::new(path);
+    let artifacts = if fs::metadata(path)?.is_dir() {
+        collect_from_dir(path)?
+    } else {
+        collect_from_manifest(path)?
+    };
+    if artifacts.is_empty() {
+        let message = format!("no proofs found in “{}”", path.display());
+        bail!(InvalidArgument(message));
+    }
+
+    Ok(artifacts)
+}
+
+fn collect_from_dir(dir: &Path) -> Result<Vec<ProofArtifacts>> {
+    let mut proofs = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "proof") {
+            proofs.push(path);
+        }
+    }
+    proofs.sort();
+
+    Ok(proofs.into_iter().map(artifacts_for_proof).collect())
+}
+
+fn collect_from_manifest(manifest: &Path) -> Result<Vec<ProofArtifacts>> {
+    let file = fs::File::open(manifest)?;
+    let entries: Vec<ManifestEntry> = serde_json::from_reader(file)?;
+    let base = manifest.parent().unwrap_or(Path::new(""));
+
+    let artifacts = entries
+        .into_iter()
+        .map(|entry| {
+            let mut artifacts = artifacts_for_proof(base.join(entry.proof));
+            if let Some(claim) = entry.claim {
+                artifacts.claim = Some(path_to_string(&base.join(claim)));
+            }
+            if let Some(parameters) = entry.parameters {
+                artifacts.parameters = path_to_string(&base.join(parameters));
+            }
+            artifacts
+        })
+        .collect();
+
+    Ok(artifacts)
+}
+
+/// The artifacts of a proof in a directory. No claim file is given: proof
+/// containers use the claim they contain, proofs in the legacy format use the
+/// claim file of the same name, see [`verify_pair`].
+fn artifacts_for_proof(proof: PathBuf) -> ProofArtifacts {
+    ProofArtifacts {
+        claim: None,
+        parameters: path_to_string(&proof.with_extension("parameters")),
+        proof: path_to_string(&proof),
+    }
+}
+
+fn path_to_string(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Verify all (Claim, Proof)-pairs in parallel. The results are in the same
+/// order as the given artifacts.
+pub fn verify(artifacts: &[ProofArtifacts], expected: &ExpectedClaim) -> Vec<Result<bool>> {
+    artifacts
+        .par_iter()
+        .map(|artifacts| verify_pair(artifacts, expected))
+        .collect()
+}
+
+fn verify_pair(artifacts: &ProofArtifacts, expected: &ExpectedClaim) -> Result<bool> {
+    let default_claim = path_to_string(&Path::new(&artifacts.proof).with_extension("claim"));
+    let (claim, proof, stark) = artifacts.read_with_default_claim(&default_claim)?;
+    expected.check(&claim)?;
+    expected.check_security_level(&stark)?;
+
+    Ok(triton_vm::verify(stark, &claim, &proof))
+}
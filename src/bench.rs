@@ -0,0 +1,35 @@
+use anyhow::Result;
+use sysinfo::System;
+use triton_vm::prelude::Program;
+
+/// A program that spins long enough to reach the padded height given as its
+/// only public input, as log₂. Supports log₂ of padded heights from 16 to 31.
+const SPIN: &str = include_str!("spin.tasm");
+
+/// The smallest log₂ of a padded height the [spin program](program) supports.
+pub const MIN_LOG2_PADDED_HEIGHT: u32 = 16;
+
+/// The largest log₂ of a padded height the [spin program](program) supports.
+pub const MAX_LOG2_PADDED_HEIGHT: u32 = 31;
+
+/// The program that is proven to benchmark the prover. Its execution trace has
+/// the padded height given as its only public input, as log₂.
+pub fn program() -> Result<Program> {
+    Ok(Program::from_code(SPIN)?)
+}
+
+/// The memory available to this process, in bytes. Respects the memory limit
+/// of the process's control group, if any.
+pub fn available_memory_in_bytes() -> u64 {
+    let mut system = System::new();
+    system.refresh_memory();
+    let available_memory = system.available_memory();
+    let Some(limits) = system.cgroup_limits() else {
+        return available_memory;
+    };
+
+    // The control group's “free memory” counts its page cache as used, even
+    // though it can be reclaimed. Its resident set size does not.
+    let available_in_cgroup = limits.total_memory.saturating_sub(limits.rss);
+    available_memory.min(available_in_cgroup)
+}
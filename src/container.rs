@@ -0,0 +1,139 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use serde::Deserialize;
+use serde::Serialize;
+use triton_vm::prelude::Claim;
+use triton_vm::prelude::Proof;
+use triton_vm::prelude::Stark;
+
+/// The first bytes of every proof container. Distinguishes containers from
+/// proofs in the legacy format, which are nothing but the bincode-encoded
+/// [`Proof`].
+pub const MAGIC: [u8; 8] = *b"TRITONPF";
+
+/// The version of the container layout. Increment on any change to
+/// [`Header`] or [`ProofContainer`].
+pub const FORMAT_VERSION: u32 = 2;
+
+/// A self-describing file holding everything needed to verify a proof.
+///
+/// The file starts with the [magic bytes](MAGIC), followed by the bincode-encoded
+/// [`Header`], followed by the bincode-encoded container itself. The header
+/// can always be read, which allows meaningful diagnostics if the rest of the
+/// file can not.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ProofContainer {
+    pub stark: Stark,
+    pub claim: Claim,
+    pub proof: Proof,
+}
+
+/// The versions identifying the layout of a proof container and of the proof in
+/// it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Header {
+    pub format_version: u32,
+
+    /// The [version of the proof format](triton_vm::proof::CURRENT_VERSION) of
+    /// the Triton VM that produced the proof. Proofs of other versions can't be
+    /// verified.
+    pub proof_version: u32,
+}
+
+impl ProofContainer {
+    pub fn is_container(bytes: &[u8]) -> bool {
+        bytes.starts_with(&MAGIC)
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let header = Header {
+            format_version: FORMAT_VERSION,
+            proof_version: triton_vm::proof::CURRENT_VERSION,
+        };
+
+        let mut bytes = MAGIC.to_vec();
+        bincode::serialize_into(&mut bytes, &header)?;
+        bincode::serialize_into(&mut bytes, self)?;
+
+        Ok(bytes)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let (_, container) = Self::decode_with_header(bytes)?;
+        Ok(container)
+    }
+
+    /// Like [`decode`](Self::decode), additionally returning the [`Header`].
+    pub fn decode_with_header(bytes: &[u8]) -> Result<(Header, Self)> {
+        let Some(mut bytes) = bytes.strip_prefix(&MAGIC) else {
+            bail!(MalformedProof("not a proof container".to_string()));
+        };
+        let header: Header =
+            bincode::deserialize_from(&mut bytes).context("malformed proof container header")?;
+
+        if header.format_version != FORMAT_VERSION {
+            bail!(UnsupportedVersion::Format(header.format_version));
+        }
+        if header.proof_version != triton_vm::proof::CURRENT_VERSION {
+            bail!(UnsupportedVersion::Proof(header.proof_version));
+        }
+
+        let container = bincode::deserialize(bytes).context("malformed proof container")?;
+        Ok((header, container))
+    }
+}
+
+/// Some proof file is neither a proof container nor a proof in the legacy
+/// format.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MalformedProof(pub String);
+
+impl Display for MalformedProof {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for MalformedProof {}
+
+/// Some file is of a version this Triton CLI or Triton VM can't handle.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum UnsupportedVersion {
+    /// The [format version](Header::format_version) of a proof container.
+    Format(u32),
+
+    /// The [proof version](Header::proof_version) of a proof container.
+    Proof(u32),
+
+    /// The version of a claim.
+    Claim(u32),
+}
+
+impl Display for UnsupportedVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let current_proof_version = triton_vm::proof::CURRENT_VERSION;
+        match self {
+            Self::Format(version) => write!(
+                f,
+                "unsupported proof container format version {version} \
+                 (supported: {FORMAT_VERSION})"
+            ),
+            Self::Proof(version) => write!(
+                f,
+                "proof was produced for proof version {version}, but this Triton VM \
+                 supports only proof version {current_proof_version}"
+            ),
+            Self::Claim(version) => write!(
+                f,
+                "the claim is about proof version {version}, but this Triton VM supports \
+                 only proof version {current_proof_version}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UnsupportedVersion {}
@@ -0,0 +1,256 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use anyhow::bail;
+use serde::Serialize;
+use triton_vm::isa::instruction::AnInstruction;
+use triton_vm::isa::parser::InstructionToken;
+use triton_vm::prelude::InstructionError;
+use triton_vm::prelude::VMState;
+
+use crate::args;
+use crate::args::InvalidArgument;
+use crate::args::STDIO;
+
+/// How often every instruction of a program was executed, and which way every
+/// branching instruction went how often.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Coverage {
+    /// Indexed by address.
+    hits: Vec<u64>,
+
+    /// Indexed by address. See [`Branch`] for which way is which.
+    branches: Vec<[u64; 2]>,
+}
+
+/// The ways a branching instruction can go.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+enum Branch {
+    /// Instruction `skiz` did not skip the next instruction, or instruction
+    /// `recurse_or_return` recursed.
+    Continue = 0,
+
+    /// Instruction `skiz` skipped the next instruction, or instruction
+    /// `recurse_or_return` returned.
+    Leave = 1,
+}
+
+/// The source code of a program, with the line of every instruction and label.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Source {
+    file: String,
+    instructions: Vec<SourceInstruction>,
+    labels: Vec<SourceLabel>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct SourceInstruction {
+    address: usize,
+
+    /// 1-based
+    line: usize,
+    is_branching: bool,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct SourceLabel {
+    name: String,
+
+    /// The address of the first instruction following the label.
+    address: usize,
+
+    /// 1-based
+    line: usize,
+}
+
+/// How much of a program's source is covered, like the summary lines of an
+/// LCOV report.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize)]
+pub struct CoverageSummary {
+    pub file: String,
+    pub lines_found: usize,
+    pub lines_hit: usize,
+    pub labels_found: usize,
+    pub labels_hit: usize,
+    pub branches_found: usize,
+    pub branches_hit: usize,
+}
+
+impl Coverage {
+    /// Execute the current instruction, recording it and, if it branches,
+    /// which way it went. An instruction that fails is recorded, too, but since
+    /// it went neither way, its branches are not.
+    pub fn step(&mut self, state: &mut VMState) -> Result<(), InstructionError> {
+        let address = state.instruction_pointer;
+        let jump_stack_len = state.jump_stack.len();
+        let instruction = state.current_instruction();
+        if self.hits.len() <= address {
+            self.hits.resize(address + 1, 0);
+            self.branches.resize(address + 1, [0; 2]);
+        }
+        self.hits[address] += 1;
+        state.step()?;
+
+        let branch = match instruction {
+            Ok(AnInstruction::Skiz) if state.instruction_pointer == address + 1 => Branch::Continue,
+            Ok(AnInstruction::Skiz) => Branch::Leave,
+            Ok(AnInstruction::RecurseOrReturn) if state.jump_stack.len() < jump_stack_len => {
+                Branch::Leave
+            }
+            Ok(AnInstruction::RecurseOrReturn) => Branch::Continue,
+            _ => return Ok(()),
+        };
+        self.branches[address][branch as usize] += 1;
+
+        Ok(())
+    }
+
+    fn hits(&self, address: usize) -> u64 {
+        self.hits.get(address).copied().unwrap_or_default()
+    }
+
+    fn branches(&self, address: usize) -> [u64; 2] {
+        self.branches.get(address).copied().unwrap_or_default()
+    }
+}
+
+impl Source {
+    /// Read the source of the program in the given file. Reading from stdin is
+    /// not supported, since the program itself is read from there.
+    pub fn read(file: &str) -> Result<Self> {
+        if file == STDIO {
+            let message = "coverage requires the program to be read from a file";
+            bail!(InvalidArgument(message.into()));
+        }
+        let code = args::read_string(file)?;
+        let Ok((_, tokens)) = triton_vm::isa::parser::tokenize(&code) else {
+            // the program itself is parsed first, which reports any problems
+            let message = format!("“{file}” is not a program");
+            bail!(InvalidArgument(message));
+        };
+
+        let newlines = code.match_indices('\n').map(|(i, _)| i).collect::<Vec<_>>();
+        let line = |token: &str| {
+            let offset = code.len() - token.len();
+            newlines.partition_point(|&newline| newline < offset) + 1
+        };
+        let mut instructions = vec![];
+        let mut labels = vec![];
+        let mut address = 0;
+        for token in tokens {
+            match token {
+                InstructionToken::Instruction(instruction, token) => {
+                    let is_branching = matches!(
+                        instruction,
+                        AnInstruction::Skiz | AnInstruction::RecurseOrReturn
+                    );
+                    instructions.push(SourceInstruction {
+                        address,
+                        line: line(token),
+                        is_branching,
+                    });
+                    address += instruction.size();
+                }
+                InstructionToken::Label(name, token) => labels.push(SourceLabel {
+                    name,
+                    address,
+                    line: line(token),
+                }),
+                _ => (),
+            }
+        }
+
+        let source = Self {
+            file: file.to_string(),
+            instructions,
+            labels,
+        };
+        Ok(source)
+    }
+
+    /// Every line with at least one instruction, together with how often its
+    /// most executed instruction was executed, ordered by line.
+    fn line_hits(&self, coverage: &Coverage) -> Vec<(usize, u64)> {
+        let mut line_hits: Vec<(usize, u64)> = vec![];
+        for instruction in &self.instructions {
+            let hits = coverage.hits(instruction.address);
+            match line_hits.last_mut() {
+                Some((line, line_hit)) if *line == instruction.line => {
+                    *line_hit = (*line_hit).max(hits);
+                }
+                _ => line_hits.push((instruction.line, hits)),
+            }
+        }
+
+        line_hits
+    }
+
+    /// The coverage as an LCOV tracefile. Labels are reported as functions,
+    /// executed as often as the instruction following them. Instructions
+    /// `skiz` and `recurse_or_return` are reported as branches: branch 0 is
+    /// executing the next instruction or recursing, branch 1 is skipping the
+    /// next instruction or returning, respectively.
+    pub fn lcov(&self, coverage: &Coverage) -> String {
+        let mut lcov = String::new();
+        let _ = writeln!(lcov, "TN:");
+        let _ = writeln!(lcov, "SF:{}", self.file);
+        for label in &self.labels {
+            let _ = writeln!(lcov, "FN:{},{}", label.line, label.name);
+        }
+        for label in &self.labels {
+            let _ = writeln!(lcov, "FNDA:{},{}", coverage.hits(label.address), label.name);
+        }
+        let summary = self.summary(coverage);
+        let _ = writeln!(lcov, "FNF:{}", summary.labels_found);
+        let _ = writeln!(lcov, "FNH:{}", summary.labels_hit);
+
+        for instruction in self.instructions.iter().filter(|i| i.is_branching) {
+            let is_executed = coverage.hits(instruction.address) > 0;
+            for (branch, taken) in coverage.branches(instruction.address).iter().enumerate() {
+                let taken = if is_executed {
+                    taken.to_string()
+                } else {
+                    String::from("-")
+                };
+                let (line, block) = (instruction.line, instruction.address);
+                let _ = writeln!(lcov, "BRDA:{line},{block},{branch},{taken}");
+            }
+        }
+        let _ = writeln!(lcov, "BRF:{}", summary.branches_found);
+        let _ = writeln!(lcov, "BRH:{}", summary.branches_hit);
+
+        for (line, hits) in self.line_hits(coverage) {
+            let _ = writeln!(lcov, "DA:{line},{hits}");
+        }
+        let _ = writeln!(lcov, "LF:{}", summary.lines_found);
+        let _ = writeln!(lcov, "LH:{}", summary.lines_hit);
+        lcov.push_str("end_of_record\n");
+
+        lcov
+    }
+
+    /// The totals of the [LCOV tracefile](Self::lcov).
+    pub fn summary(&self, coverage: &Coverage) -> CoverageSummary {
+        let line_hits = self.line_hits(coverage);
+        let branches = self
+            .instructions
+            .iter()
+            .filter(|i| i.is_branching)
+            .flat_map(|i| coverage.branches(i.address))
+            .collect::<Vec<_>>();
+
+        CoverageSummary {
+            file: self.file.clone(),
+            lines_found: line_hits.len(),
+            lines_hit: line_hits.iter().filter(|&&(_, hits)| hits > 0).count(),
+            labels_found: self.labels.len(),
+            labels_hit: self
+                .labels
+                .iter()
+                .filter(|label| coverage.hits(label.address) > 0)
+                .count(),
+            branches_found: branches.len(),
+            branches_hit: branches.iter().filter(|&&taken| taken > 0).count(),
+        }
+    }
+}
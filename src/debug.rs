@@ -0,0 +1,335 @@
+use std::collections::BTreeSet;
+use std::io::BufRead;
+use std::io::Write;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use itertools::Itertools;
+use triton_vm::isa::instruction::Instruction;
+use triton_vm::prelude::InstructionError;
+use triton_vm::prelude::VMState;
+
+use crate::args::Limits;
+use crate::args::StateDump;
+
+const HELP: &str = "\
+step [n]         execute the next n instructions (default: 1)
+continue         execute until a breakpoint is hit or the program terminates
+break <loc>      set a breakpoint at a label or instruction address
+delete <loc>     remove the breakpoint at a label or instruction address
+breakpoints      list all breakpoints
+stack            print the op stack, top element first
+jumpstack        print the jump stack, top element first
+ram              print all initialized RAM cells
+sponge           print the Sponge state
+output           print the public output produced so far
+state            print the entire VM state
+help             print this message
+quit             end the debugging session";
+
+/// A line-oriented, scriptable debugger for Triton VM programs.
+///
+/// Commands are read line by line. Everything requested is written to the
+/// output. Errors concerning the commands themselves are written there, too,
+/// but don't end the session. Command “help” lists the available commands.
+#[derive(Debug, Clone)]
+pub struct Debugger {
+    state: VMState,
+
+    /// Instruction addresses at which command “continue” stops, in addition to
+    /// the breakpoints of the program itself.
+    breakpoints: BTreeSet<u64>,
+
+    /// The reason Triton VM crashed, if it did. Once set, execution cannot
+    /// progress.
+    crash: Option<InstructionError>,
+
+    /// Execution stops once these are exceeded.
+    limits: Limits,
+
+    /// Where to write the VM state to if Triton VM crashes.
+    state_dump: Option<StateDump>,
+}
+
+/// How Triton VM ended up when a debugging [session](Debugger::session) ended.
+#[derive(Debug, Clone)]
+pub enum SessionEnd {
+    /// The program halted.
+    Halted,
+
+    /// Triton VM crashed.
+    Crashed(InstructionError),
+
+    /// The program neither halted nor crashed, for example, because the session
+    /// was ended early or the cycle limit was reached.
+    Paused,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum DebugCommand {
+    Step(usize),
+    Continue,
+    Break(String),
+    Delete(String),
+    Breakpoints,
+    Stack,
+    JumpStack,
+    Ram,
+    Sponge,
+    Output,
+    State,
+    Help,
+    Quit,
+}
+
+impl Debugger {
+    pub fn new(state: VMState) -> Self {
+        Self {
+            state,
+            breakpoints: BTreeSet::new(),
+            crash: None,
+            limits: Limits::default(),
+            state_dump: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    #[must_use]
+    pub fn with_state_dump(mut self, dump: Option<StateDump>) -> Self {
+        self.state_dump = dump;
+        self
+    }
+
+    /// Read commands from `input` until it is exhausted or the session is ended
+    /// explicitly. If `prompt` is set, a prompt is written before reading each
+    /// command.
+    pub fn session(
+        mut self,
+        input: impl BufRead,
+        mut output: impl Write,
+        prompt: bool,
+    ) -> Result<SessionEnd> {
+        self.print_location(&mut output)?;
+
+        let mut lines = input.lines();
+        loop {
+            if prompt {
+                write!(output, "(triton) ")?;
+                output.flush()?;
+            }
+            let Some(line) = lines.next() else {
+                break;
+            };
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let result = match DebugCommand::parse(&line) {
+                Ok(DebugCommand::Quit) => break,
+                Ok(command) => self.execute(command, &mut output),
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                writeln!(output, "error: {err}")?;
+            }
+        }
+
+        let end = match self.crash {
+            Some(err) => SessionEnd::Crashed(err),
+            None if self.state.halting => SessionEnd::Halted,
+            None => SessionEnd::Paused,
+        };
+        Ok(end)
+    }
+
+    fn execute(&mut self, command: DebugCommand, output: &mut impl Write) -> Result<()> {
+        match command {
+            DebugCommand::Step(num_steps) => {
+                for _ in 0..num_steps {
+                    if !self.step(output)? {
+                        break;
+                    }
+                }
+                self.print_location(output)?;
+            }
+            DebugCommand::Continue => {
+                while self.step(output)? && !self.is_at_breakpoint() {}
+                self.print_location(output)?;
+            }
+            DebugCommand::Break(location) => {
+                let address = self.resolve(&location)?;
+                self.breakpoints.insert(address);
+            }
+            DebugCommand::Delete(location) => {
+                let address = self.resolve(&location)?;
+                if !self.breakpoints.remove(&address) {
+                    bail!("no breakpoint at “{location}”");
+                }
+            }
+            DebugCommand::Breakpoints => {
+                for &address in &self.breakpoints {
+                    let label = self.state.program.label_for_address(address);
+                    writeln!(output, "{address}: {label}")?;
+                }
+            }
+            DebugCommand::Stack => {
+                for (i, element) in self.state.op_stack.stack.iter().rev().enumerate() {
+                    writeln!(output, "st{i}: {element}")?;
+                }
+            }
+            DebugCommand::JumpStack => {
+                for (origin, destination) in self.state.jump_stack.iter().rev() {
+                    let label = self.state.program.label_for_address(destination.value());
+                    writeln!(output, "{origin} -> {destination} ({label})")?;
+                }
+            }
+            DebugCommand::Ram => {
+                let ram = self
+                    .state
+                    .ram
+                    .iter()
+                    .sorted_by_key(|(address, _)| address.value());
+                for (address, value) in ram {
+                    writeln!(output, "{address}: {value}")?;
+                }
+            }
+            DebugCommand::Sponge => match &self.state.sponge {
+                Some(sponge) => writeln!(output, "{}", sponge.state.iter().join(", "))?,
+                None => writeln!(output, "uninitialized")?,
+            },
+            DebugCommand::Output => {
+                writeln!(output, "{}", self.state.public_output.iter().join(", "))?;
+            }
+            DebugCommand::State => writeln!(output, "{}", self.state)?,
+            DebugCommand::Help => writeln!(output, "{HELP}")?,
+            DebugCommand::Quit => (),
+        }
+
+        Ok(())
+    }
+
+    /// Execute a single instruction, if possible. Returns whether an
+    /// instruction was executed. If the state cannot be dumped after a crash,
+    /// this is reported in the output.
+    fn step(&mut self, output: &mut impl Write) -> Result<bool> {
+        if self.state.halting || self.crash.is_some() {
+            return Ok(false);
+        }
+        if self
+            .limits
+            .check_cycle_count(self.state.cycle_count)
+            .is_err()
+        {
+            return Ok(false);
+        }
+        if let Err(err) = self.state.step() {
+            self.crash = Some(err);
+            if let Some(dump) = &self.state_dump
+                && let Err(dump_err) = dump.write(&self.state)
+            {
+                let path = &dump.path;
+                writeln!(
+                    output,
+                    "error: could not dump VM state to “{path}”: {dump_err}"
+                )?;
+            }
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    fn is_at_breakpoint(&self) -> bool {
+        let Ok(address) = u64::try_from(self.state.instruction_pointer) else {
+            return false;
+        };
+
+        self.breakpoints.contains(&address) || self.state.program.is_breakpoint(address)
+    }
+
+    /// Turn a label or an instruction address into an instruction address.
+    fn resolve(&self, location: &str) -> Result<u64> {
+        if let Ok(address) = location.parse::<u64>() {
+            return Ok(address);
+        }
+
+        let program = &self.state.program;
+        let num_addresses = u64::try_from(program.len_bwords())?;
+        (0..num_addresses)
+            .find(|&address| program.label_for_address(address) == location)
+            .ok_or_else(|| anyhow!("unknown label “{location}”"))
+    }
+
+    fn print_location(&self, output: &mut impl Write) -> Result<()> {
+        let cycle = self.state.cycle_count;
+        if let Some(err) = &self.crash {
+            writeln!(output, "[cycle {cycle}] crashed: {err}")?;
+            return Ok(());
+        }
+        if self.state.halting {
+            writeln!(output, "[cycle {cycle}] halted")?;
+            return Ok(());
+        }
+        if let Err(limit_exceeded) = self.limits.check_cycle_count(cycle) {
+            writeln!(output, "[cycle {cycle}] stopped: {limit_exceeded}")?;
+            return Ok(());
+        }
+
+        let ip = self.state.instruction_pointer;
+        let instruction = match self.state.current_instruction() {
+            Ok(Instruction::Call(address)) => {
+                let label = self.state.program.label_for_address(address.value());
+                format!("call {label}")
+            }
+            Ok(instruction) => instruction.to_string(),
+            Err(err) => err.to_string(),
+        };
+        writeln!(output, "[cycle {cycle}] {ip}: {instruction}")?;
+
+        Ok(())
+    }
+}
+
+impl DebugCommand {
+    fn parse(line: &str) -> Result<Self> {
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            bail!("empty command");
+        };
+        let argument = words.next();
+        if let Some(superfluous) = words.next() {
+            bail!("unexpected argument “{superfluous}”");
+        }
+        let location = || {
+            argument
+                .map(String::from)
+                .ok_or_else(|| anyhow!("missing location"))
+        };
+
+        let command = match command {
+            "s" | "step" => Self::Step(argument.map(str::parse).transpose()?.unwrap_or(1)),
+            "c" | "continue" => Self::Continue,
+            "b" | "break" => Self::Break(location()?),
+            "d" | "delete" => Self::Delete(location()?),
+            "breakpoints" => Self::Breakpoints,
+            "stack" => Self::Stack,
+            "jumpstack" => Self::JumpStack,
+            "ram" => Self::Ram,
+            "sponge" => Self::Sponge,
+            "output" => Self::Output,
+            "state" => Self::State,
+            "h" | "help" => Self::Help,
+            "q" | "quit" => Self::Quit,
+            _ => bail!("unknown command “{command}” – try “help”"),
+        };
+
+        Ok(command)
+    }
+}
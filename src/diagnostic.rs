@@ -0,0 +1,190 @@
+use itertools::Itertools;
+use nom_language::error::VerboseErrorKind;
+use triton_vm::isa::instruction::ALL_INSTRUCTION_NAMES;
+use triton_vm::isa::parser::InstructionToken;
+use triton_vm::isa::parser::ParseError;
+
+/// Suggestions are only made if they are at most this many edits away.
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// A problem with a specific location in the source code of a program, in the
+/// style of a compiler error.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Diagnostic {
+    pub message: String,
+
+    /// 1-based
+    pub line: usize,
+
+    /// 1-based, counted in characters
+    pub column: usize,
+
+    /// The entire line containing the problem.
+    pub source_line: String,
+
+    /// The number of characters to highlight, starting at the column.
+    pub span_len: usize,
+
+    /// How to fix the problem, if known.
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    /// Turn the error produced when parsing `code` into diagnostics, ordered by
+    /// their location in the code.
+    pub fn from_parse_error(code: &str, error: &ParseError) -> Vec<Self> {
+        let mut problems = Vec::new();
+        for (remainder, kind) in &error.errors.errors {
+            let Some(offset) = offset_in(code, remainder) else {
+                continue;
+            };
+            let message = match kind {
+                VerboseErrorKind::Context(context) => Some(*context),
+                _ => None,
+            };
+            problems.push((offset, message));
+        }
+
+        // Entries at the same location describe the same problem. The first
+        // context is the most specific one.
+        problems.sort_by_key(|&(offset, _)| offset);
+        problems
+            .into_iter()
+            .chunk_by(|&(offset, _)| offset)
+            .into_iter()
+            .map(|(offset, group)| {
+                let message = group.filter_map(|(_, message)| message).next();
+                Self::new(code, offset, message.unwrap_or("syntax error"))
+            })
+            .collect()
+    }
+
+    /// Suggestions depend only on the code at the problem's location, not on
+    /// the message, which is up to the parser.
+    fn new(code: &str, offset: usize, message: &str) -> Self {
+        let mut offset = offset;
+        let called_label = code[offset..]
+            .strip_prefix("call")
+            .filter(|rest| rest.starts_with(char::is_whitespace))
+            .map(str::trim_start);
+        if let Some(label) = called_label {
+            // point at the label, not at instruction `call`
+            offset = code.len() - label.len();
+        }
+
+        let line_start = code[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = code[offset..].find('\n').map_or(code.len(), |i| offset + i);
+        let word = code[offset..line_end]
+            .split(|c: char| c.is_whitespace())
+            .next()
+            .unwrap_or_default();
+
+        let is_instruction = ALL_INSTRUCTION_NAMES.contains(&word);
+        let is_label_declaration = word.ends_with(':');
+        let help = if called_label.is_some() {
+            suggest(word, &defined_labels(code)).map(|label| format!("did you mean “{label}”?"))
+        } else if !is_instruction && !is_label_declaration {
+            suggest(word, &ALL_INSTRUCTION_NAMES)
+                .map(|instruction| format!("did you mean instruction “{instruction}”?"))
+        } else {
+            None
+        };
+
+        Self {
+            message: message.to_string(),
+            line: code[..offset].matches('\n').count() + 1,
+            column: code[line_start..offset].chars().count() + 1,
+            source_line: code[line_start..line_end].trim_end().to_string(),
+            span_len: word.chars().count().max(1),
+            help,
+        }
+    }
+
+    /// Render the diagnostic for the program in the given file. If `color` is
+    /// set, ANSI escape codes are used.
+    pub fn render(&self, file: &str, color: bool) -> String {
+        let paint = |style: &str, text: &str| {
+            if color {
+                format!("{style}{text}{RESET}")
+            } else {
+                text.to_string()
+            }
+        };
+
+        let line_number = self.line.to_string();
+        let indent = " ".repeat(line_number.len());
+        let gutter = paint(BLUE, &format!("{indent} |"));
+        let arrow = paint(BLUE, &format!("{indent}-->"));
+        let numbered_gutter = paint(BLUE, &format!("{line_number} |"));
+        let caret_indent = " ".repeat(self.column - 1);
+        let carets = paint(RED, &"^".repeat(self.span_len));
+
+        let mut lines = vec![
+            format!("{}: {}", paint(RED, "error"), paint(BOLD, &self.message)),
+            format!("{arrow} {file}:{}:{}", self.line, self.column),
+            gutter.clone(),
+            format!("{numbered_gutter} {}", self.source_line),
+            format!("{gutter} {caret_indent}{carets}"),
+        ];
+        if let Some(help) = &self.help {
+            let equals = paint(BLUE, &format!("{indent} ="));
+            lines.push(format!("{equals} {}: {help}", paint(BOLD, "help")));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// The offset of `part` in `whole` if `part` is a sub-slice of `whole`.
+fn offset_in(whole: &str, part: &str) -> Option<usize> {
+    let offset = part.as_ptr().addr().checked_sub(whole.as_ptr().addr())?;
+    (offset + part.len() <= whole.len()).then_some(offset)
+}
+
+fn defined_labels(code: &str) -> Vec<String> {
+    let Ok((_, tokens)) = triton_vm::isa::parser::tokenize(code) else {
+        return vec![];
+    };
+
+    tokens
+        .into_iter()
+        .filter_map(|token| match token {
+            InstructionToken::Label(label, _) => Some(label),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The candidate most similar to `word`, unless all are too different.
+fn suggest<'c>(word: &str, candidates: &'c [impl AsRef<str>]) -> Option<&'c str> {
+    candidates
+        .iter()
+        .map(AsRef::as_ref)
+        .map(|candidate| (edit_distance(word, candidate), candidate))
+        .filter(|&(distance, _)| 0 < distance && distance <= MAX_SUGGESTION_DISTANCE)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect_vec();
+    let mut previous_row = (0..=b.len()).collect_vec();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, &b_char) in b.iter().enumerate() {
+            let substitution = previous_row[j] + usize::from(a_char != b_char);
+            let insertion = row[j] + 1;
+            let deletion = previous_row[j + 1] + 1;
+            row.push(substitution.min(insertion).min(deletion));
+        }
+        previous_row = row;
+    }
+
+    previous_row[b.len()]
+}
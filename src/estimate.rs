@@ -0,0 +1,133 @@
+use anyhow::Result;
+use triton_vm::prelude::Digest;
+use triton_vm::prelude::Stark;
+use triton_vm::table::NUM_QUOTIENT_SEGMENTS;
+use triton_vm::table::master_table::MasterAuxTable;
+use triton_vm::table::master_table::MasterMainTable;
+use triton_vm::twenty_first::prelude::x_field_element::EXTENSION_DEGREE;
+
+const BYTES_PER_BFE: u64 = 8;
+const BYTES_PER_XFE: u64 = BYTES_PER_BFE * EXTENSION_DEGREE as u64;
+const BYTES_PER_DIGEST: u64 = BYTES_PER_BFE * Digest::LEN as u64;
+
+/// The number of base field elements the encoding of a proof item takes in
+/// addition to its payload, like its discriminant and length prefixes.
+const ITEM_OVERHEAD: f64 = 3.0;
+
+/// The expected size of the bincode-encoded proof for an execution trace of the
+/// given padded height, in bytes, like reported by command “inspect”.
+///
+/// Most of the proof is fixed by the padded height and the STARK parameters.
+/// Only the size of the Merkle authentication structures depends on the
+/// randomly sampled indices of the revealed rows. For those, the expected size
+/// is used.
+pub fn proof_size_in_bytes(stark: &Stark, padded_height: usize) -> Result<u64> {
+    let fri = stark.fri(padded_height)?;
+    let domain_len = fri.domain.len() as f64;
+    let num_rounds = fri.num_rounds();
+    let num_checks = stark.num_collinearity_checks as f64;
+    let main_width = MasterMainTable::NUM_COLUMNS as f64;
+    let aux_width = MasterAuxTable::NUM_COLUMNS as f64;
+    let num_segments = NUM_QUOTIENT_SEGMENTS as f64;
+    let xfe = EXTENSION_DEGREE as f64;
+    let digest = Digest::LEN as f64;
+    let auth_structure =
+        |num_leafs: f64| digest * authentication_structure_len(num_leafs, num_checks);
+
+    // the main, auxiliary, and quotient Merkle trees, then one per FRI round
+    let num_merkle_roots = (3 + num_rounds + 1) as f64;
+    let merkle_roots = num_merkle_roots * digest;
+    let out_of_domain_rows = 2.0 * (main_width + aux_width) * xfe + num_segments * xfe;
+    let last_codeword = (fri.domain.len() >> num_rounds) as f64 * xfe;
+    let last_polynomial = (fri.last_round_max_degree() + 1) as f64 * xfe;
+
+    // The first FRI round reveals two codeword elements per collinearity check,
+    // all other rounds but the last reveal one.
+    let num_fri_responses = num_rounds + 1;
+    let fri_responses = (0..num_rounds)
+        .map(|round| domain_len / f64::from(1 << round))
+        .chain([domain_len])
+        .map(|num_leafs| num_checks * xfe + auth_structure(num_leafs))
+        .sum::<f64>();
+
+    let revealed_rows = num_checks * (main_width + aux_width * xfe + num_segments * xfe);
+    let revealed_rows_auth_structures = 3.0 * auth_structure(domain_len);
+
+    // log₂ of the padded height, 4 out-of-domain rows, 1 row of out-of-domain
+    // quotient segments, the last codeword and polynomial, and 3 revealed rows
+    // with their authentication structures
+    let num_items = (1 + 4 + 1 + 2 + 6 + num_fri_responses) as f64 + num_merkle_roots;
+    let num_bfes = 1.0
+        + merkle_roots
+        + out_of_domain_rows
+        + last_codeword
+        + last_polynomial
+        + fri_responses
+        + revealed_rows
+        + revealed_rows_auth_structures
+        + num_items * ITEM_OVERHEAD;
+
+    // bincode encodes the length of the vector, then every element
+    Ok(BYTES_PER_BFE * (1 + num_bfes.ceil() as u64))
+}
+
+/// The expected number of digests in the authentication structure of a Merkle
+/// tree with the given number of leafs, if the given number of leafs is
+/// revealed. The revealed leafs are sampled uniformly, with replacement.
+///
+/// On every level of the tree, a node needs to be included if exactly one of it
+/// and its sibling is on the path from a revealed leaf to the root.
+fn authentication_structure_len(num_leafs: f64, num_revealed_leafs: f64) -> f64 {
+    let mut len = 0.0;
+    let mut num_nodes = num_leafs;
+    while num_nodes >= 2.0 {
+        let not_on_path = (1.0 - 1.0 / num_nodes).powf(num_revealed_leafs);
+        let neither_on_path = (1.0 - 2.0 / num_nodes).powf(num_revealed_leafs);
+        len += num_nodes * (not_on_path - neither_on_path);
+        num_nodes /= 2.0;
+    }
+
+    len
+}
+
+/// A rough estimate of the memory the prover needs at its peak, in bytes, for
+/// an execution trace of the given padded height.
+///
+/// Assumes that the prover keeps the low-degree extensions of the main and
+/// auxiliary tables in memory, which it does if enough memory is available.
+/// If not, the prover recomputes them on the fly, which is slower but requires
+/// less memory. Memory the operating system attributes to the process
+/// otherwise, like the program and the execution trace, is not included.
+///
+/// Not validated systematically. Command “bench” reports the estimate next to
+/// the measured maximum resident set size.
+pub fn peak_memory_in_bytes(stark: &Stark, padded_height: usize) -> Result<u64> {
+    let fri = stark.fri(padded_height)?;
+    let fri_domain_len = fri.domain.len() as u64;
+    let max_degree = usize::try_from(stark.max_degree(padded_height))?;
+    let quotient_domain_len = max_degree.next_power_of_two() as u64;
+    let evaluation_domain_len = fri_domain_len.max(quotient_domain_len);
+    let randomized_trace_len = (padded_height + stark.num_trace_randomizers).next_power_of_two();
+    let trace_len = randomized_trace_len as u64 / 2;
+
+    let main_width = MasterMainTable::NUM_COLUMNS as u64;
+    let aux_width = MasterAuxTable::NUM_COLUMNS as u64;
+    let num_segments = NUM_QUOTIENT_SEGMENTS as u64;
+
+    // the tables, their interpolants, and their low-degree extensions
+    let tables_len = trace_len + randomized_trace_len as u64 + evaluation_domain_len;
+    let main_table = tables_len * main_width * BYTES_PER_BFE;
+    let aux_table = tables_len * aux_width * BYTES_PER_XFE;
+
+    // the quotient segments over the quotient domain and the FRI domain
+    let quotient_segments = (quotient_domain_len + fri_domain_len) * num_segments * BYTES_PER_XFE;
+
+    // A Merkle tree has about twice as many nodes as leafs. The trees of the
+    // later FRI rounds together are about as big as the tree of the first one.
+    let merkle_trees = (3 + 2) * 2 * fri_domain_len * BYTES_PER_DIGEST;
+
+    // the codewords of the DEEP update, their combination, and all FRI rounds
+    let codewords = (3 + 1 + 2) * evaluation_domain_len * BYTES_PER_XFE;
+
+    Ok(main_table + aux_table + quotient_segments + merkle_trees + codewords)
+}
@@ -0,0 +1,52 @@
+//! The exit codes of Triton CLI.
+//!
+//! The exit codes are stable: a code is never re-assigned to a different
+//! meaning. New kinds of failures might get new codes. Until then, they exit
+//! with [`FAILURE`].
+
+/// The command completed successfully. For command “verify”, this includes
+/// that the proof holds up.
+pub const SUCCESS: u8 = 0;
+
+/// Anything not covered by a more specific exit code.
+pub const FAILURE: u8 = 1;
+
+/// The arguments are invalid. This includes problems detected by the argument
+/// parser itself.
+pub const INVALID_ARGUMENT: u8 = 2;
+
+/// The program exceeded some [limit](crate::args::Limits).
+pub const LIMIT_EXCEEDED: u8 = 3;
+
+/// The proof does not hold up.
+pub const VERIFICATION_REJECTED: u8 = 4;
+
+/// The claim is not about what the verifier
+/// [expects](crate::args::Expectations).
+pub const CLAIM_MISMATCH: u8 = 5;
+
+/// A file could not be read or written.
+pub const IO: u8 = 6;
+
+/// The program could not be parsed.
+pub const PROGRAM: u8 = 7;
+
+/// The public input could not be parsed.
+pub const INPUT: u8 = 8;
+
+/// Some json or binary file could not be deserialized, or is of an unsupported
+/// version.
+pub const DESERIALIZATION: u8 = 9;
+
+/// Triton VM crashed during execution of the program.
+pub const VM: u8 = 10;
+
+/// Proof generation failed for a reason other than a crash of Triton VM.
+pub const PROVING: u8 = 11;
+
+/// Some total of a profile increased by more than its threshold.
+pub const PROFILE_REGRESSED: u8 = 12;
+
+/// The STARK parameters of the proof provide less security than the verifier
+/// [expects](crate::args::Expectations::min_security_level).
+pub const INSUFFICIENT_SECURITY: u8 = 13;
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use itertools::Itertools;
+use triton_vm::execution_trace_profiler::ExecutionTraceProfile;
+
+use crate::args::ProfileTable;
+use crate::profile::TableHeights;
+
+/// The bottom-most frame of every stack. Stands for the program as a whole.
+const ROOT: &str = "program";
+
+const WIDTH: f64 = 1200.0;
+const PADDING: f64 = 10.0;
+const TITLE_HEIGHT: f64 = 30.0;
+const FRAME_HEIGHT: f64 = 16.0;
+const FONT_SIZE: f64 = 12.0;
+
+/// A generous estimate of the width of one character in the flamegraph's font.
+const CHAR_WIDTH: f64 = 7.0;
+
+/// The execution profile as folded stacks: one line per call stack, with the
+/// frames separated by semicolons, followed by the stack's weight. The weight
+/// is how much the innermost subroutine, excluding its calls, contributes to
+/// the height of the given table.
+///
+/// ```text
+/// program;main;hash_pair 42
+/// ```
+pub fn folded(profile: &ExecutionTraceProfile, table: ProfileTable) -> String {
+    stacks(profile, table)
+        .into_iter()
+        .map(|(stack, weight)| format!("{stack} {weight}"))
+        .join("\n")
+}
+
+/// The self-weight of every distinct call stack, sorted by stack. Stacks
+/// without weight are omitted.
+fn stacks(profile: &ExecutionTraceProfile, table: ProfileTable) -> BTreeMap<String, u64> {
+    let height = |heights| i64::from(TableHeights::from(heights).height(table));
+
+    // index 0 is the root; the profile's lines follow
+    let mut paths = vec![ROOT.to_string()];
+    let mut self_weights = vec![height(profile.total)];
+    let mut open_spans = vec![];
+    for line in &profile.profile {
+        open_spans.truncate(line.call_depth);
+        let parent = open_spans.last().copied().unwrap_or(0);
+        let weight = height(line.table_heights_stop - line.table_heights_start);
+        self_weights[parent] -= weight;
+
+        open_spans.push(paths.len());
+        paths.push(format!("{};{}", paths[parent], line.label));
+        self_weights.push(weight);
+    }
+
+    let mut stacks = BTreeMap::new();
+    for (path, weight) in paths.into_iter().zip(self_weights) {
+        let Ok(weight) = u64::try_from(weight) else {
+            continue;
+        };
+        if weight > 0 {
+            *stacks.entry(path).or_default() += weight;
+        }
+    }
+
+    stacks
+}
+
+/// A frame of the flamegraph, together with the frames called from it.
+#[derive(Debug, Default)]
+struct Frame {
+    weight: u64,
+    children: BTreeMap<String, Frame>,
+}
+
+impl Frame {
+    fn new(stacks: &BTreeMap<String, u64>) -> Self {
+        let mut root = Self::default();
+        for (stack, &weight) in stacks {
+            let mut frame = &mut root;
+            for name in stack.split(';').skip(1) {
+                frame.weight += weight;
+                frame = frame.children.entry(name.to_string()).or_default();
+            }
+            frame.weight += weight;
+        }
+
+        root
+    }
+
+    fn depth(&self) -> usize {
+        let depth_of_children = self.children.values().map(Self::depth).max();
+        depth_of_children.map_or(1, |depth| depth + 1)
+    }
+}
+
+/// The execution profile as a flamegraph: one box per call stack, as wide as
+/// its weight, stacked on top of the box of its caller. Hovering over a box
+/// reveals its exact weight.
+pub fn svg(profile: &ExecutionTraceProfile, table: ProfileTable) -> String {
+    let root = Frame::new(&stacks(profile, table));
+    let height = TITLE_HEIGHT + root.depth() as f64 * FRAME_HEIGHT + 2.0 * PADDING;
+    let title = match table {
+        ProfileTable::Processor => "Cycle Count".to_string(),
+        _ => format!("Height of the {table:?} Table"),
+    };
+    let unit = match table {
+        ProfileTable::Processor => "cycles",
+        _ => "rows",
+    };
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{height}" viewBox="0 0 {WIDTH} {height}" font-family="monospace" font-size="{FONT_SIZE}">"#,
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect width="100%" height="100%" fill="rgb(250,250,250)"/>"#
+    );
+    let _ = writeln!(
+        svg,
+        r#"<text x="{}" y="{}" text-anchor="middle" font-size="{}">{title}</text>"#,
+        WIDTH / 2.0,
+        PADDING + FONT_SIZE,
+        FONT_SIZE + 4.0,
+    );
+
+    let mut canvas = Canvas {
+        svg,
+        height,
+        total_weight: root.weight,
+        unit,
+    };
+    canvas.draw(ROOT, &root, 0, 0);
+    canvas.svg.push_str("</svg>");
+
+    canvas.svg
+}
+
+#[derive(Debug)]
+struct Canvas {
+    svg: String,
+    height: f64,
+    total_weight: u64,
+    unit: &'static str,
+}
+
+impl Canvas {
+    /// Draw the frame and, on top of it, the frames called from it. The offset
+    /// is the combined weight of all frames to the left.
+    fn draw(&mut self, name: &str, frame: &Frame, depth: usize, offset: u64) {
+        if self.total_weight == 0 {
+            return;
+        }
+        let scale = (WIDTH - 2.0 * PADDING) / self.total_weight as f64;
+        let width = frame.weight as f64 * scale;
+        if width < 0.1 {
+            return;
+        }
+        let x = PADDING + offset as f64 * scale;
+        let y = self.height - PADDING - (depth + 1) as f64 * FRAME_HEIGHT;
+
+        let share = 100.0 * frame.weight as f64 / self.total_weight as f64;
+        let tooltip = format!("{name} ({} {}, {share:.2}%)", frame.weight, self.unit);
+        let label = label(name, width);
+        let _ = writeln!(
+            self.svg,
+            r#"<g><title>{}</title><rect x="{x:.2}" y="{y:.2}" width="{width:.2}" height="{}" fill="{}" rx="2"/><text x="{:.2}" y="{:.2}">{}</text></g>"#,
+            escape(&tooltip),
+            FRAME_HEIGHT - 1.0,
+            color(name),
+            x + 3.0,
+            y + FONT_SIZE,
+            escape(&label),
+        );
+
+        let mut child_offset = offset;
+        for (child_name, child) in &frame.children {
+            self.draw(child_name, child, depth + 1, child_offset);
+            child_offset += child.weight;
+        }
+    }
+}
+
+/// The name, shortened to fit into a box of the given width.
+fn label(name: &str, width: f64) -> String {
+    let num_chars = ((width - 6.0) / CHAR_WIDTH).max(0.0) as usize;
+    if name.chars().count() <= num_chars {
+        return name.to_string();
+    }
+    if num_chars < 3 {
+        return String::new();
+    }
+
+    let prefix = name.chars().take(num_chars - 2).collect::<String>();
+    format!("{prefix}..")
+}
+
+/// A warm color that is always the same for the same name.
+fn color(name: &str) -> String {
+    // FNV-1a
+    let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    let [red, green, blue] = [hash % 50 + 205, (hash >> 8) % 230, (hash >> 16) % 55];
+
+    format!("rgb({red},{green},{blue})")
+}
+
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            c => c.to_string(),
+        })
+        .join("")
+}
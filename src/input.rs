@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use anyhow::Result;
+use anyhow::bail;
+use itertools::Itertools;
+use triton_vm::prelude::BFieldElement;
+use triton_vm::prelude::Digest;
+use triton_vm::twenty_first::error::ParseBFieldElementError;
+
+use crate::args::InputFormat;
+
+/// Some input is not a list of elements in the expected
+/// [format](InputFormat).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MalformedInput(pub String);
+
+impl Display for MalformedInput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for MalformedInput {}
+
+/// Parse the text as a list of base field elements in the given format.
+/// Extension field elements and digests are flattened into their base field
+/// elements.
+pub fn parse(text: &str, format: InputFormat) -> Result<Vec<BFieldElement>> {
+    let elements = match format {
+        InputFormat::Decimal => tokens(text).map(str::parse).try_collect()?,
+        InputFormat::Hex => tokens(text).map(parse_hex).try_collect()?,
+        InputFormat::U32 => tokens(text).map(parse_u32).try_collect()?,
+        InputFormat::Json => parse_json(text)?,
+        InputFormat::Xfe => parse_xfes(text)?,
+        InputFormat::Digest => parse_digests(text)?
+            .into_iter()
+            .flat_map(|digest| digest.reversed().values())
+            .collect(),
+    };
+
+    Ok(elements)
+}
+
+/// The non-empty parts of the text, separated by commas or whitespace.
+fn tokens(text: &str) -> impl Iterator<Item = &str> {
+    text.split(is_separator).filter(|token| !token.is_empty())
+}
+
+fn is_separator(c: char) -> bool {
+    c == ',' || c.is_whitespace()
+}
+
+fn parse_hex(token: &str) -> Result<BFieldElement> {
+    let (sign, digits) = match token.strip_prefix('-') {
+        Some(digits) => (-1, digits),
+        None => (1, token),
+    };
+    let digits = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+        .unwrap_or(digits);
+
+    // `i128::from_str_radix` accepts a sign of its own
+    if digits.starts_with(['+', '-']) {
+        let message = format!("“{token}” is not a hexadecimal number");
+        bail!(MalformedInput(message));
+    }
+    let value = i128::from_str_radix(digits, 16).map_err(ParseBFieldElementError::ParseIntError)?;
+
+    Ok(bfe_from_i128(sign * value)?)
+}
+
+fn parse_u32(token: &str) -> Result<BFieldElement, MalformedInput> {
+    let Ok(value) = token.parse::<u32>() else {
+        return Err(MalformedInput(format!("“{token}” is not a u32")));
+    };
+
+    Ok(BFieldElement::from(value))
+}
+
+/// Map the integer into the field like [`BFieldElement::from_str`] does:
+/// negative numbers are counted down from the modulus, and numbers that are
+/// not canonical are rejected.
+///
+/// [`BFieldElement::from_str`]: std::str::FromStr::from_str
+fn bfe_from_i128(value: i128) -> Result<BFieldElement, ParseBFieldElementError> {
+    let p = i128::from(BFieldElement::P);
+    let normalized = match value {
+        n if n <= -p || n >= p => return Err(ParseBFieldElementError::NotCanonical(value)),
+        n if n < 0 => n + p,
+        n => n,
+    };
+    let normalized =
+        u64::try_from(normalized).map_err(|_| ParseBFieldElementError::NotCanonical(value))?;
+
+    Ok(BFieldElement::new(normalized))
+}
+
+/// A json array of numbers or strings of decimal numbers. Strings are needed
+/// for elements that other tools can't represent as json numbers.
+fn parse_json(text: &str) -> Result<Vec<BFieldElement>> {
+    let elements: Vec<serde_json::Value> = serde_json::from_str(text)
+        .map_err(|err| MalformedInput(format!("input is not a json array: {err}")))?;
+
+    let mut bfes = Vec::with_capacity(elements.len());
+    for element in elements {
+        let bfe = match &element {
+            serde_json::Value::Number(number) => match number.as_i128() {
+                Some(number) => bfe_from_i128(number)?,
+                None => bail!(MalformedInput(format!("“{number}” is not an integer"))),
+            },
+            serde_json::Value::String(string) => string.trim().parse()?,
+            _ => bail!(MalformedInput(format!("“{element}” is not an element"))),
+        };
+        bfes.push(bfe);
+    }
+
+    Ok(bfes)
+}
+
+/// Extension field elements as triples of coefficients in parentheses, for
+/// example, `(1, 2, 3), (4, 5, 6)`. The coefficients are ordered such that
+/// after instruction `read_io 3`, the extension field element is on the stack
+/// like instructions `xx_add` & co. expect it.
+fn parse_xfes(text: &str) -> Result<Vec<BFieldElement>> {
+    let mut bfes = Vec::new();
+    let mut rest = text.trim_start_matches(is_separator);
+    while !rest.is_empty() {
+        let Some((triple, tail)) = rest.strip_prefix('(').and_then(|rest| rest.split_once(')'))
+        else {
+            let message = format!("expected extension field element “(c0, c1, c2)”: “{rest}”");
+            bail!(MalformedInput(message));
+        };
+        let coefficients: Vec<BFieldElement> = triple
+            .split(',')
+            .map(|coefficient| coefficient.trim().parse())
+            .try_collect()?;
+        let Ok([c0, c1, c2]) = <[_; 3]>::try_from(coefficients) else {
+            let message = format!("extension field element “({triple})” needs 3 coefficients");
+            bail!(MalformedInput(message));
+        };
+        bfes.extend([c2, c1, c0]);
+        rest = tail.trim_start_matches(is_separator);
+    }
+
+    Ok(bfes)
+}
+
+/// Digests in hexadecimal, as printed by command “digest”, separated by commas
+/// or whitespace.
+///
+/// When flattened into public input, the elements are ordered such that after
+/// instruction `read_io 5`, the digest is on the stack like instruction
+/// `merkle_step` & co. expect it.
+pub fn parse_digests(text: &str) -> Result<Vec<Digest>> {
+    let mut digests = Vec::new();
+    for token in tokens(text) {
+        let hex = token.strip_prefix("0x").unwrap_or(token);
+        let Ok(digest) = Digest::try_from_hex(hex) else {
+            bail!(MalformedInput(format!("“{token}” is not a digest")));
+        };
+        digests.push(digest);
+    }
+
+    Ok(digests)
+}
+
+/// The initial content of RAM, one `address: value` pair per line. Both address
+/// and value are base field elements in decimal. Empty lines are ignored.
+pub fn parse_ram(text: &str) -> Result<HashMap<BFieldElement, BFieldElement>> {
+    let mut ram = HashMap::new();
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let Some((address, value)) = line.split_once(':') else {
+            let message = format!("expected “address: value”, found “{line}”");
+            bail!(MalformedInput(message));
+        };
+        let address: BFieldElement = address.trim().parse()?;
+        let value = value.trim().parse()?;
+        if ram.insert(address, value).is_some() {
+            let message = format!("address {address} is given more than once");
+            bail!(MalformedInput(message));
+        }
+    }
+
+    Ok(ram)
+}
+
+#[cfg(test)]
+mod tests {
+    use triton_vm::prelude::bfe;
+
+    use super::*;
+
+    #[test]
+    fn parse_hex_accepts_one_sign_before_the_prefix() {
+        assert_eq!(bfe!(26), parse_hex("0x1a").unwrap());
+        assert_eq!(bfe!(26), parse_hex("1A").unwrap());
+        assert_eq!(bfe!(-3), parse_hex("-0x3").unwrap());
+        assert_eq!(bfe!(-3), parse_hex("-3").unwrap());
+
+        for token in ["0x-3", "--3", "-+3", "+3", "0x+3", "-0x-3"] {
+            let err = parse_hex(token).unwrap_err();
+            assert!(err.is::<MalformedInput>(), "{token}");
+        }
+    }
+}
@@ -0,0 +1,821 @@
+//! The library behind Triton CLI. Everything the command line interface can
+//! do is available here, too: loading programs, their input, and their
+//! non-determinism, running, proving, verifying, and reading and writing claims
+//! and proofs.
+//!
+//! The functions at the top level correspond to the commands of the command
+//! line interface. They take the [parsed arguments](args) and produce a
+//! [report]. Like on the command line, a path “-” refers to stdin or stdout.
+//! Apart from that, they never print anything.
+//!
+//! Most commands also have a counterpart that takes values instead of paths,
+//! like [`run_program`] for [`run`] or [`verify_proof`] for [`verify`]. These
+//! counterparts neither read nor write any files.
+
+use std::io::Write;
+use std::ops::ControlFlow;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::time::Instant;
+
+use anyhow::Result;
+use anyhow::bail;
+use triton_vm::aet::AlgebraicExecutionTrace;
+use triton_vm::prelude::BFieldElement;
+use triton_vm::prelude::Claim;
+use triton_vm::prelude::NonDeterminism;
+use triton_vm::prelude::Program;
+use triton_vm::prelude::Proof;
+use triton_vm::prelude::PublicInput;
+use triton_vm::prelude::Stark;
+use triton_vm::prelude::TableId;
+use triton_vm::prelude::VM;
+use triton_vm::prelude::VMError;
+use triton_vm::prelude::VMState;
+
+use crate::args::AetFormat;
+use crate::args::BenchArgs;
+use crate::args::Expectations;
+use crate::args::ExpectedClaim;
+use crate::args::InitialInput;
+use crate::args::InvalidArgument;
+use crate::args::Limits;
+use crate::args::ProfileArgs;
+use crate::args::ProfileDiffArgs;
+use crate::args::ProfileFormat;
+use crate::args::ProofArtifacts;
+use crate::args::RunArgs;
+use crate::args::StarkArgs;
+use crate::args::StateDump;
+use crate::args::TraceFormat;
+use crate::coverage::Coverage;
+use crate::profile::PerformanceProfile;
+use crate::profile::Profile;
+use crate::report::AetReport;
+use crate::report::BenchReport;
+use crate::report::BenchResult;
+use crate::report::BenchStop;
+use crate::report::ClaimSummary;
+use crate::report::DigestReport;
+use crate::report::ErrorSummary;
+use crate::report::EstimateReport;
+use crate::report::InspectReport;
+use crate::report::PairVerdict;
+use crate::report::ProfileDiffReport;
+use crate::report::ProofSummary;
+use crate::report::ProveReport;
+use crate::report::RunReport;
+use crate::report::TraceReport;
+use crate::report::VerifyBatchReport;
+use crate::report::VerifyReport;
+use crate::trace::TraceRow;
+use crate::trace::TraceWriter;
+
+pub mod aet;
+pub mod args;
+pub mod batch;
+pub mod bench;
+pub mod container;
+pub mod coverage;
+pub mod debug;
+pub mod diagnostic;
+pub mod estimate;
+pub mod exit_code;
+pub mod flamegraph;
+pub mod input;
+pub mod output;
+pub mod profile;
+pub mod profile_diff;
+pub mod report;
+pub mod trace;
+
+/// Run a program to completion. If `profile` arguments are given, the report
+/// includes an execution profile rendered accordingly. If a `coverage` file is
+/// given, an LCOV report of the executed instructions is written to it.
+pub fn run(
+    args: RunArgs,
+    profile: Option<ProfileArgs>,
+    coverage: Option<&str>,
+) -> Result<RunReport> {
+    let limits = args.limits;
+    let dump_path = args.dump_state_on_error.clone();
+    let source_path = args.separate_files.program.clone();
+    let (program, input, non_determinism) = args.parse()?;
+    let dump = state_dump(dump_path, &input, &non_determinism);
+
+    let source = match (coverage, source_path) {
+        (None, _) => None,
+        (Some(_), Some(path)) => Some(coverage::Source::read(&path)?),
+        (Some(_), None) => {
+            let message = "coverage requires the program's source, not an initial state";
+            bail!(InvalidArgument(message.into()));
+        }
+    };
+    let mut recorded_coverage = source.as_ref().map(|_| Coverage::default());
+    let result = run_and_cover(
+        program,
+        input,
+        non_determinism,
+        limits,
+        profile,
+        recorded_coverage.as_mut(),
+    );
+
+    // The coverage is most interesting if the program crashed, so it is written
+    // in any case – before the error is passed on.
+    let (Some(path), Some(source), Some(recorded_coverage)) = (coverage, source, recorded_coverage)
+    else {
+        return result.map_err(|err| dump_state(err, dump.as_ref()));
+    };
+    let written = args::write_bytes(path, source.lcov(&recorded_coverage).as_bytes());
+    let mut report = match (result, written) {
+        (Ok(report), Ok(())) => report,
+        (Ok(_), Err(err)) => return Err(err),
+        (Err(err), Ok(())) => return Err(dump_state(err, dump.as_ref())),
+        (Err(err), Err(write_err)) => {
+            let err = err.context(format!("could not write coverage to “{path}”: {write_err}"));
+            return Err(dump_state(err, dump.as_ref()));
+        }
+    };
+    report.coverage = Some(source.summary(&recorded_coverage));
+
+    Ok(report)
+}
+
+/// Like [`run`], but for a program, its input, and its non-determinism that
+/// are already loaded. Crashes of Triton VM are not dumped.
+pub fn run_program(
+    program: Program,
+    input: PublicInput,
+    non_determinism: NonDeterminism,
+    limits: Limits,
+    profile: Option<ProfileArgs>,
+) -> Result<RunReport> {
+    run_and_cover(program, input, non_determinism, limits, profile, None)
+}
+
+/// The part of [`run`] that executes the program, recording every executed
+/// instruction if `coverage` is given. Crashes of Triton VM are not dumped.
+fn run_and_cover(
+    program: Program,
+    input: PublicInput,
+    non_determinism: NonDeterminism,
+    limits: Limits,
+    profile: Option<ProfileArgs>,
+    coverage: Option<&mut Coverage>,
+) -> Result<RunReport> {
+    let needs_padded_height = profile.is_some() || limits.max_padded_height.is_some();
+    if !needs_padded_height {
+        let state = execute_and_cover(program, input, non_determinism, limits, coverage)?;
+        return Ok(RunReport {
+            output: state.public_output,
+            cycle_count: state.cycle_count,
+            padded_height: None,
+            profile: None,
+            coverage: None,
+        });
+    }
+
+    // Tracing and profiling are unbounded and can't record coverage, so if the
+    // run must be bounded or covered, the program is executed first. Everything
+    // reported is taken from the traced or profiled run.
+    if limits != Limits::default() || coverage.is_some() {
+        execute_and_cover(
+            program.clone(),
+            input.clone(),
+            non_determinism.clone(),
+            limits,
+            coverage,
+        )?;
+    }
+    let report = match profile {
+        Some(profile_args) => {
+            let (output, profile) = VM::profile(program, input, non_determinism)?;
+            RunReport {
+                output,
+                cycle_count: profile.total.processor,
+                padded_height: Some(profile.padded_height),
+                profile: Some(Profile::execution(&profile, profile_args)),
+                coverage: None,
+            }
+        }
+        None => {
+            let (aet, output) = VM::trace_execution(program, input, non_determinism)?;
+            let cycle_count = aet.height_of_table(TableId::Processor);
+            RunReport {
+                output,
+                cycle_count: u32::try_from(cycle_count)?,
+                padded_height: Some(aet.padded_height()),
+                profile: None,
+                coverage: None,
+            }
+        }
+    };
+    if let Some(padded_height) = report.padded_height {
+        limits.check_padded_height(padded_height)?;
+    }
+
+    Ok(report)
+}
+
+/// Run the program to completion or until some [limit](Limits) is exceeded.
+/// In contrast to [`VM::run`], the terminal [`VMState`] is returned, which
+/// gives access to _e.g._ the cycle count.
+pub fn execute(
+    program: Program,
+    input: PublicInput,
+    non_determinism: NonDeterminism,
+    limits: Limits,
+) -> Result<VMState> {
+    execute_and_cover(program, input, non_determinism, limits, None)
+}
+
+/// Like [`execute`], additionally recording every executed instruction if
+/// `coverage` is given.
+fn execute_and_cover(
+    program: Program,
+    input: PublicInput,
+    non_determinism: NonDeterminism,
+    limits: Limits,
+    mut coverage: Option<&mut Coverage>,
+) -> Result<VMState> {
+    let mut state = VMState::new(program, input, non_determinism);
+    while !state.halting {
+        limits.check_cycle_count(state.cycle_count)?;
+        let step = match coverage.as_deref_mut() {
+            Some(coverage) => coverage.step(&mut state),
+            None => state.step().map(drop),
+        };
+        if let Err(err) = step {
+            return Err(VMError::new(err, state).into());
+        }
+    }
+
+    Ok(state)
+}
+
+/// Trace the execution of a program, respecting the [limits](Limits).
+fn trace_within_limits(
+    program: Program,
+    input: PublicInput,
+    non_determinism: NonDeterminism,
+    limits: Limits,
+) -> Result<(AlgebraicExecutionTrace, Vec<BFieldElement>)> {
+    // Tracing execution is unbounded. Executing first guarantees termination.
+    if limits != Limits::default() {
+        execute(
+            program.clone(),
+            input.clone(),
+            non_determinism.clone(),
+            limits,
+        )?;
+    }
+    let (aet, output) = VM::trace_execution(program, input, non_determinism)?;
+    limits.check_padded_height(aet.padded_height())?;
+
+    Ok((aet, output))
+}
+
+/// Where to dump the state of Triton VM if it crashes, if anywhere.
+fn state_dump(
+    path: Option<String>,
+    input: &PublicInput,
+    non_determinism: &NonDeterminism,
+) -> Option<StateDump> {
+    path.map(|path| StateDump {
+        path,
+        initial_input: InitialInput::new(input, non_determinism),
+    })
+}
+
+/// If the error is a crash of Triton VM and a dump is given, write the state of
+/// the crashed VM to it. Either way, the error is passed on.
+fn dump_state(err: impl Into<anyhow::Error>, dump: Option<&StateDump>) -> anyhow::Error {
+    let err = err.into();
+    let Some(dump) = dump else {
+        return err;
+    };
+    let Some(vm_error) = err.downcast_ref::<VMError>() else {
+        return err;
+    };
+
+    match dump.write(&vm_error.vm_state) {
+        Ok(()) => err,
+        Err(dump_err) => {
+            let path = &dump.path;
+            err.context(format!("could not dump VM state to “{path}”: {dump_err}"))
+        }
+    }
+}
+
+/// Run a program to completion, writing its [execution trace](trace::TraceRow)
+/// in the given format. If Triton VM crashes, the trace ends with the failing
+/// instruction.
+///
+/// The writer is created only once the arguments are parsed, such that no
+/// trace file is left behind if they are invalid.
+pub fn trace<W: Write>(
+    args: RunArgs,
+    format: TraceFormat,
+    create_writer: impl FnOnce() -> Result<W>,
+) -> Result<TraceReport> {
+    let limits = args.limits;
+    let dump_path = args.dump_state_on_error.clone();
+    let (program, input, non_determinism) = args.parse()?;
+    let dump = state_dump(dump_path, &input, &non_determinism);
+
+    let writer = create_writer()?;
+    trace_program(program, input, non_determinism, limits, format, writer)
+        .map_err(|err| dump_state(err, dump.as_ref()))
+}
+
+/// Like [`trace()`], but for a program, its input, and its non-determinism that
+/// are already loaded. Crashes of Triton VM are not dumped.
+pub fn trace_program(
+    program: Program,
+    input: PublicInput,
+    non_determinism: NonDeterminism,
+    limits: Limits,
+    format: TraceFormat,
+    writer: impl Write,
+) -> Result<TraceReport> {
+    let mut writer = TraceWriter::new(writer, format)?;
+    let mut state = VMState::new(program, input, non_determinism);
+    while !state.halting {
+        limits.check_cycle_count(state.cycle_count)?;
+        writer.write(&TraceRow::new(&state))?;
+        if let Err(err) = state.step() {
+            writer.flush()?;
+            return Err(VMError::new(err, state).into());
+        }
+    }
+    writer.flush()?;
+
+    let report = TraceReport {
+        output: state.public_output,
+        cycle_count: state.cycle_count,
+    };
+    Ok(report)
+}
+
+/// Export the tables of the algebraic execution trace to a directory. See
+/// [`aet::write`] for details.
+pub fn export_aet(args: RunArgs, dir: &str, format: AetFormat) -> Result<AetReport> {
+    let limits = args.limits;
+    let dump_path = args.dump_state_on_error.clone();
+    let (program, input, non_determinism) = args.parse()?;
+    let dump = state_dump(dump_path, &input, &non_determinism);
+
+    export_program_aet(program, input, non_determinism, limits, dir, format)
+        .map_err(|err| dump_state(err, dump.as_ref()))
+}
+
+/// Like [`export_aet`], but for a program, its input, and its non-determinism
+/// that are already loaded. Crashes of Triton VM are not dumped.
+pub fn export_program_aet(
+    program: Program,
+    input: PublicInput,
+    non_determinism: NonDeterminism,
+    limits: Limits,
+    dir: &str,
+    format: AetFormat,
+) -> Result<AetReport> {
+    let (aet, _) = trace_within_limits(program, input, non_determinism, limits)?;
+
+    let files = aet::write(&aet::tables(&aet), Path::new(dir), format)?;
+    let report = AetReport {
+        dir: dir.to_string(),
+        files,
+        heights: aet::heights(&aet),
+        padded_height: aet.padded_height(),
+    };
+    Ok(report)
+}
+
+/// Compare two profiles saved with `--profile-format json`. Totals that
+/// increased by more than their threshold are reported as
+/// [regressions](ProfileDiffReport::regressions).
+pub fn profile_diff(args: ProfileDiffArgs) -> Result<ProfileDiffReport> {
+    args::ensure_stdin_is_read_at_most_once([Some(args.old.as_str()), Some(args.new.as_str())])?;
+    let old = profile_diff::read(&args.old)?;
+    let new = profile_diff::read(&args.new)?;
+    let diff = profile_diff::diff(&old, &new)?;
+    let regressions = profile_diff::regressions(&diff.totals, &args)?;
+
+    let report = ProfileDiffReport {
+        old: args.old,
+        new: args.new,
+        totals: diff.totals,
+        entries: diff.entries,
+        regressions,
+    };
+    Ok(report)
+}
+
+/// Prove correct execution of a program, then write the claim and the proof to
+/// the given [artifacts](ProofArtifacts). If a `profile` format is given, the
+/// report includes a performance profile in that format.
+pub fn prove(
+    args: RunArgs,
+    stark: StarkArgs,
+    artifacts: ProofArtifacts,
+    profile: Option<ProfileFormat>,
+) -> Result<ProveReport> {
+    artifacts.ensure_stdout_is_written_at_most_once()?;
+    if let Some(format) = profile {
+        profile::ensure_performance_profile_format(format)?;
+    }
+    let stark = stark.parse()?;
+    let limits = args.limits;
+    let dump_path = args.dump_state_on_error.clone();
+    let (program, input, non_determinism) = args.parse()?;
+    let dump = state_dump(dump_path, &input, &non_determinism);
+
+    triton_vm::profiler::start("Triton VM – Prove");
+    let start = Instant::now();
+    let (claim, proof, cycle_count) =
+        prove_within_limits(program, input, non_determinism, limits, stark)
+            .map_err(|err| dump_state(err, dump.as_ref()))?;
+    let duration_in_ns = elapsed_in_ns(start);
+
+    let padded_height = proof.padded_height()?;
+    let profile = match profile {
+        Some(format) => {
+            let fri_domain_len = fri_domain_length(&stark, padded_height)?;
+            let profile = triton_vm::profiler::finish()
+                .with_cycle_count(cycle_count)
+                .with_padded_height(padded_height)
+                .with_fri_domain_len(fri_domain_len);
+            let mut profile = Profile::performance(&profile, format)?;
+            if let Profile::Performance(profile) = &mut profile {
+                profile.duration_in_ns = duration_in_ns;
+                profile.cycle_count = Some(cycle_count);
+                profile.padded_height = Some(padded_height);
+                profile.fri_domain_len = Some(fri_domain_len);
+            }
+            Some(profile)
+        }
+        None => None,
+    };
+
+    artifacts.write(&claim, &proof, &stark)?;
+
+    let report = ProveReport {
+        claim: artifacts.claim_file().to_string(),
+        proof: artifacts.proof,
+        stark,
+        output: claim.output,
+        cycle_count,
+        padded_height,
+        profile,
+    };
+    Ok(report)
+}
+
+/// Like [`prove`], but for a program, its input, and its non-determinism that
+/// are already loaded. Returns the claim and the proof instead of writing them.
+/// Crashes of Triton VM are not dumped.
+pub fn prove_program(
+    program: Program,
+    input: PublicInput,
+    non_determinism: NonDeterminism,
+    limits: Limits,
+    stark: Stark,
+) -> Result<(Claim, Proof)> {
+    let (claim, proof, _) = prove_within_limits(program, input, non_determinism, limits, stark)?;
+    Ok((claim, proof))
+}
+
+/// Prove correct execution of a program, respecting the [limits](Limits).
+/// Also returns the cycle count.
+fn prove_within_limits(
+    program: Program,
+    input: PublicInput,
+    non_determinism: NonDeterminism,
+    limits: Limits,
+    stark: Stark,
+) -> Result<(Claim, Proof, usize)> {
+    let claim = Claim::about_program(&program).with_input(input.clone());
+    let (aet, public_output) = trace_within_limits(program, input, non_determinism, limits)?;
+    let claim = claim.with_output(public_output);
+    let proof = stark.prove(&claim, &aet)?;
+
+    Ok((claim, proof, aet.processor_trace.nrows()))
+}
+
+/// Estimate the cost of proving correct execution of a program, without
+/// proving. See [`estimate::proof_size_in_bytes`] and
+/// [`estimate::peak_memory_in_bytes`] for how the costs are estimated.
+pub fn estimate(args: RunArgs, stark: StarkArgs) -> Result<EstimateReport> {
+    let stark = stark.parse()?;
+    let limits = args.limits;
+    let dump_path = args.dump_state_on_error.clone();
+    let (program, input, non_determinism) = args.parse()?;
+    let dump = state_dump(dump_path, &input, &non_determinism);
+
+    estimate_program(program, input, non_determinism, limits, stark)
+        .map_err(|err| dump_state(err, dump.as_ref()))
+}
+
+/// Like [`estimate()`], but for a program, its input, and its non-determinism
+/// that are already loaded. Crashes of Triton VM are not dumped.
+pub fn estimate_program(
+    program: Program,
+    input: PublicInput,
+    non_determinism: NonDeterminism,
+    limits: Limits,
+    stark: Stark,
+) -> Result<EstimateReport> {
+    let (aet, _) = trace_within_limits(program, input, non_determinism, limits)?;
+    let padded_height = aet.padded_height();
+
+    let heights = aet::heights(&aet);
+
+    // among equally tall tables, the first one dominates
+    let dominating_table = heights
+        .iter()
+        .rev()
+        .max_by_key(|h| h.height)
+        .map(|h| h.table.clone())
+        .unwrap_or_default();
+
+    let report = EstimateReport {
+        stark,
+        cycle_count: aet.processor_trace.nrows(),
+        heights,
+        dominating_table,
+        padded_height,
+        fri_domain_len: fri_domain_length(&stark, padded_height)?,
+        proof_size_in_bytes: estimate::proof_size_in_bytes(&stark, padded_height)?,
+        peak_memory_in_bytes: estimate::peak_memory_in_bytes(&stark, padded_height)?,
+    };
+    Ok(report)
+}
+
+/// Prove a [built-in program](bench::program) for increasing padded heights,
+/// measuring the time and memory proving takes. Stops before the first padded
+/// height for which the prover would need more memory than is available. Every
+/// result is passed to `progress` as soon as it is known.
+///
+/// If proving fails for some padded height, the benchmark ends, and the
+/// report includes the [error](BenchReport::error) as well as all results up
+/// to that point.
+pub fn bench(args: BenchArgs, progress: impl FnMut(&BenchResult)) -> Result<BenchReport> {
+    let BenchArgs {
+        min_log2_padded_height,
+        max_log2_padded_height,
+        max_memory,
+        stark,
+    } = args;
+    if min_log2_padded_height > max_log2_padded_height {
+        let message = "the smallest padded height must not exceed the largest";
+        bail!(InvalidArgument(message.into()));
+    }
+    let stark = stark.parse()?;
+    let log2_padded_heights = min_log2_padded_height..=max_log2_padded_height;
+
+    bench_program(
+        &bench::program()?,
+        stark,
+        log2_padded_heights,
+        max_memory,
+        progress,
+    )
+}
+
+/// Like [`bench()`], but proves the given program instead of the built-in one.
+/// The program must take the log₂ of the padded height as its only public
+/// input, and its execution trace should have that padded height. Unless
+/// `max_memory_in_mib` is given, the prover may use all available memory.
+pub fn bench_program(
+    program: &Program,
+    stark: Stark,
+    log2_padded_heights: RangeInclusive<u32>,
+    max_memory_in_mib: Option<u64>,
+    mut progress: impl FnMut(&BenchResult),
+) -> Result<BenchReport> {
+    let mut report = BenchReport {
+        stark,
+        results: vec![],
+        stop: None,
+        error: None,
+    };
+    for log2_padded_height in log2_padded_heights {
+        match bench_padded_height(program, &stark, log2_padded_height, max_memory_in_mib) {
+            Ok(ControlFlow::Continue(result)) => {
+                progress(&result);
+                report.results.push(result);
+            }
+            Ok(ControlFlow::Break(stop)) => {
+                report.stop = Some(stop);
+                break;
+            }
+            Err(err) => {
+                report.error = Some(ErrorSummary::new(&err));
+                break;
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Prove the program for one padded height, unless the prover would need more
+/// memory than is available.
+fn bench_padded_height(
+    program: &Program,
+    stark: &Stark,
+    log2_padded_height: u32,
+    max_memory_in_mib: Option<u64>,
+) -> Result<ControlFlow<BenchStop, BenchResult>> {
+    let padded_height = 1 << log2_padded_height;
+    let estimated_peak_memory_in_bytes = estimate::peak_memory_in_bytes(stark, padded_height)?;
+    let available_memory_in_bytes = max_memory_in_mib
+        .map_or_else(bench::available_memory_in_bytes, |mib| {
+            mib.saturating_mul(1 << 20)
+        });
+    if estimated_peak_memory_in_bytes > available_memory_in_bytes {
+        return Ok(ControlFlow::Break(BenchStop {
+            log2_padded_height,
+            estimated_peak_memory_in_bytes,
+            available_memory_in_bytes,
+        }));
+    }
+
+    triton_vm::profiler::start("Triton CLI – Bench");
+    let start = Instant::now();
+    let input = PublicInput::new(vec![log2_padded_height.into()]);
+    let claim = Claim::about_program(program).with_input(input.clone());
+    let (aet, output) = VM::trace_execution(program.clone(), input, NonDeterminism::default())?;
+    let claim = claim.with_output(output);
+    stark.prove(&claim, &aet)?;
+    let duration_in_ns = elapsed_in_ns(start);
+    let profile = PerformanceProfile::parse(&triton_vm::profiler::finish().to_string())?;
+
+    Ok(ControlFlow::Continue(BenchResult {
+        log2_padded_height,
+        padded_height: aet.padded_height(),
+        cycle_count: aet.processor_trace.nrows(),
+        fri_domain_len: fri_domain_length(stark, aet.padded_height())?,
+        duration_in_ns,
+        max_rss_in_bytes: profile.max_rss_in_bytes,
+        estimated_peak_memory_in_bytes,
+    }))
+}
+
+/// Verify a (Claim, Proof)-pair. A claim that does not meet the
+/// [expectations](Expectations) is an error, while a proof that does not hold
+/// up is reported in the [verdict](VerifyReport::verdict). If a `profile` format
+/// is given, the report includes a performance profile in that format.
+pub fn verify(
+    artifacts: ProofArtifacts,
+    expectations: Expectations,
+    profile: Option<ProfileFormat>,
+) -> Result<VerifyReport> {
+    args::ensure_stdin_is_read_at_most_once(
+        artifacts.paths().into_iter().chain(expectations.paths()),
+    )?;
+    if let Some(format) = profile {
+        profile::ensure_performance_profile_format(format)?;
+    }
+    let (claim, proof, stark) = artifacts.read()?;
+    let expected = expectations.parse()?;
+    expected.check(&claim)?;
+    expected.check_security_level(&stark)?;
+
+    triton_vm::profiler::start("Triton VM – Verify");
+    let start = Instant::now();
+    let verdict = triton_vm::verify(stark, &claim, &proof);
+    let duration_in_ns = elapsed_in_ns(start);
+    let profile = match profile {
+        Some(format) => {
+            let padded_height = proof.padded_height()?;
+            let fri_domain_len = fri_domain_length(&stark, padded_height)?;
+            let profile = triton_vm::profiler::finish()
+                .with_padded_height(padded_height)
+                .with_fri_domain_len(fri_domain_len);
+            let mut profile = Profile::performance(&profile, format)?;
+            if let Profile::Performance(profile) = &mut profile {
+                profile.duration_in_ns = duration_in_ns;
+                profile.padded_height = Some(padded_height);
+                profile.fri_domain_len = Some(fri_domain_len);
+            }
+            Some(profile)
+        }
+        None => None,
+    };
+
+    let report = VerifyReport {
+        claim: artifacts.claim,
+        proof: artifacts.proof,
+        stark,
+        verdict,
+        padded_height: proof.padded_height().ok(),
+        profile,
+    };
+    Ok(report)
+}
+
+/// Like [`verify`], but for a claim and a proof that are already loaded. A claim
+/// that does not meet the [expectations](ExpectedClaim) is an error. Returns
+/// whether the proof holds up.
+pub fn verify_proof(
+    claim: &Claim,
+    proof: &Proof,
+    stark: Stark,
+    expected: &ExpectedClaim,
+) -> Result<bool> {
+    expected.check(claim)?;
+    expected.check_security_level(&stark)?;
+
+    Ok(triton_vm::verify(stark, claim, proof))
+}
+
+/// Verify all (Claim, Proof)-pairs in a [batch](batch::collect), in parallel.
+/// Problems with individual pairs are part of the report.
+pub fn verify_batch(batch: &str, expectations: Expectations) -> Result<VerifyBatchReport> {
+    let expected = expectations.parse()?;
+    let artifacts = batch::collect(batch)?;
+    let results = batch::verify(&artifacts, &expected);
+
+    let pairs = artifacts
+        .into_iter()
+        .zip(results)
+        .map(|(artifacts, result)| PairVerdict {
+            claim: artifacts.claim,
+            proof: artifacts.proof,
+            verdict: matches!(result, Ok(true)),
+            error: result.as_ref().err().map(ErrorSummary::new),
+        })
+        .collect::<Vec<_>>();
+
+    let report = VerifyBatchReport {
+        num_pairs: pairs.len(),
+        num_verified: pairs.iter().filter(|pair| pair.verdict).count(),
+        pairs,
+    };
+    Ok(report)
+}
+
+/// Summarize a claim and, if it exists, the corresponding proof.
+pub fn inspect(artifacts: ProofArtifacts) -> Result<InspectReport> {
+    let proof_exists = artifacts.proof == args::STDIO || fs_err::exists(&artifacts.proof)?;
+    if !proof_exists {
+        return inspect_proof(artifacts.read_claim()?, None);
+    }
+
+    let (claim, proof, stark, container) = artifacts.read_with_header()?;
+    let mut report = inspect_proof(claim, Some((&proof, stark)))?;
+    if let Some(proof) = &mut report.proof {
+        proof.container = container;
+    }
+    Ok(report)
+}
+
+/// Like [`inspect`], but for a claim and, optionally, a proof that are already
+/// loaded, together with the STARK parameters the proof was produced with.
+pub fn inspect_proof(claim: Claim, proof: Option<(&Proof, Stark)>) -> Result<InspectReport> {
+    let proof = match proof {
+        Some((proof, stark)) => {
+            let padded_height = proof.padded_height().ok();
+            let fri_domain_len = padded_height.and_then(|h| fri_domain_length(&stark, h).ok());
+            let proof = ProofSummary {
+                size_in_bytes: bincode::serialized_size(proof)?,
+                padded_height,
+                fri_domain_len,
+                stark,
+                container: None,
+            };
+            Some(proof)
+        }
+        None => None,
+    };
+
+    let claim = ClaimSummary {
+        version: claim.version,
+        program_digest: claim.program_digest,
+        input_len: claim.input.len(),
+        input: claim.input,
+        output_len: claim.output.len(),
+        output: claim.output,
+    };
+    Ok(InspectReport { claim, proof })
+}
+
+/// Compute the digest of the program in the given file, exactly like
+/// [`Claim::about_program`] does.
+pub fn digest(program: String) -> Result<DigestReport> {
+    let program_digest = args::read_program(program)?.hash();
+    Ok(DigestReport { program_digest })
+}
+
+/// The time since `start`. Measured by Triton CLI, since the durations in Triton
+/// VM's performance profile are only available as rendered for humans.
+fn elapsed_in_ns(start: Instant) -> u64 {
+    u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX)
+}
+
+fn fri_domain_length(stark: &Stark, padded_height: usize) -> Result<usize> {
+    let fri = stark.fri(padded_height)?;
+    Ok(fri.domain.len())
+}
@@ -1,94 +1,339 @@
+use std::io::IsTerminal;
 use std::process::ExitCode;
 
 use anyhow::Result;
+use anyhow::bail;
+use clap::CommandFactory;
 use clap::Parser;
 use itertools::Itertools;
-use triton_vm::prelude::Claim;
-use triton_vm::prelude::Stark;
-use triton_vm::prelude::VM;
-
-use crate::args::Args;
-use crate::args::Command;
-use crate::args::Flags;
-use crate::args::ProofArtifacts;
-use crate::args::RunArgs;
-
-const SUCCESS: ExitCode = ExitCode::SUCCESS;
-const FAILURE: ExitCode = ExitCode::FAILURE;
-
-mod args;
+use triton_cli::args;
+use triton_cli::args::Args;
+use triton_cli::args::Command;
+use triton_cli::args::Flags;
+use triton_cli::args::Format;
+use triton_cli::args::InvalidArgument;
+use triton_cli::args::OutputArgs;
+use triton_cli::args::ProfileCommand;
+use triton_cli::args::ProgramParseError;
+use triton_cli::args::RunArgs;
+use triton_cli::args::STDIO;
+use triton_cli::args::StateDump;
+use triton_cli::debug::Debugger;
+use triton_cli::debug::SessionEnd;
+use triton_cli::exit_code;
+use triton_cli::profile;
+use triton_cli::report::BenchReport;
+use triton_cli::report::BenchResult;
+use triton_cli::report::ErrorKind;
+use triton_cli::report::ErrorReport;
+use triton_cli::report::Report;
+use triton_cli::report::VerifyBatchReport;
 
 fn main() -> Result<ExitCode> {
     human_panic::setup_panic!();
 
-    let Args { flags, command } = Args::parse();
-    match command {
-        Command::Run(args) => run(flags, args),
-        Command::Prove { args, artifacts } => prove(flags, args, artifacts),
-        Command::Verify(artifacts) => verify(flags, artifacts),
+    let Args { flags, command, .. } = match Args::try_parse() {
+        Ok(args) => args,
+        Err(err) => return report_argument_error(err),
+    };
+    let format = flags.format;
+    let color = flags.color;
+    let command_name = command.name();
+    let result = execute_command(flags, command);
+
+    let Err(err) = result else {
+        return result;
+    };
+    let parse_error = err.downcast_ref::<ProgramParseError>();
+    match (format, parse_error) {
+        (Format::Human, Some(parse_error)) => {
+            eprintln!("{}", parse_error.render(color.use_color_on_stderr()));
+        }
+        (Format::Human, None) => eprintln!("Error: {err:?}"),
+        (Format::Json, _) => ErrorReport::new(Some(command_name), &err).print()?,
     }
+
+    Ok(ExitCode::from(ErrorKind::of(&err).exit_code()))
 }
 
-fn run(flags: Flags, args: RunArgs) -> Result<ExitCode> {
-    let (program, input, non_determinism) = args.parse()?;
+/// The argument parser reports problems in plain text only. With “--format
+/// json”, report them like any other error instead. The arguments are parsed a
+/// second time, ignoring errors, to find out the format and the command.
+fn report_argument_error(err: clap::Error) -> Result<ExitCode> {
+    let matches = Args::command().ignore_errors(true).get_matches();
+    let format = matches.get_one::<Format>("format").copied();
+    if !err.use_stderr() || format != Some(Format::Json) {
+        err.exit();
+    }
 
-    let output = if flags.profile {
-        let (output, profile) = VM::profile(program, input, non_determinism)?;
-        println!("{profile}\n");
-        output
-    } else {
-        VM::run(program, input, non_determinism)?
+    let command_name = match matches.subcommand() {
+        Some(("verify", verify)) if verify.get_one::<String>("batch").is_some() => {
+            Some("verify_batch".to_string())
+        }
+        Some((name, command)) => match command.subcommand_name() {
+            Some(subcommand) => Some(format!("{name}_{subcommand}")),
+            None => Some(name.to_string()),
+        },
+        None => None,
     };
-    if !output.is_empty() {
-        println!("{}", output.iter().join(", "));
-    }
+    // the first paragraph is the problem, the rest explains the usage
+    let rendered = err.render().to_string();
+    let problem = rendered
+        .lines()
+        .take_while(|line| !line.trim().is_empty())
+        .map(str::trim)
+        .join(" ");
+    let problem = problem.strip_prefix("error: ").unwrap_or(&problem);
+    let err = anyhow::Error::new(InvalidArgument(problem.to_string()));
+    ErrorReport::new(command_name.as_deref(), &err).print()?;
 
-    Ok(SUCCESS)
+    Ok(ExitCode::from(exit_code::INVALID_ARGUMENT))
 }
 
-fn prove(flags: Flags, args: RunArgs, artifacts: ProofArtifacts) -> Result<ExitCode> {
-    let (program, input, non_determinism) = args.parse()?;
-
-    triton_vm::profiler::start("Triton VM – Prove");
-    let claim = Claim::about_program(&program).with_input(input.clone());
-    let (aet, public_output) = VM::trace_execution(program, input, non_determinism)?;
-    let claim = claim.with_output(public_output);
-    let proof = Stark::default().prove(&claim, &aet)?;
-
-    if flags.profile {
-        let padded_height = aet.padded_height();
-        let profile = triton_vm::profiler::finish()
-            .with_cycle_count(aet.processor_trace.nrows())
-            .with_padded_height(padded_height)
-            .with_fri_domain_len(fri_domain_length(padded_height)?);
-        println!("{profile}");
+fn execute_command(flags: Flags, command: Command) -> Result<ExitCode> {
+    let Flags {
+        profile,
+        profile_args,
+        profile_out,
+        format,
+        ..
+    } = flags;
+    let output = match &command {
+        Command::Run { output, .. } => output.clone(),
+        _ => OutputArgs::default(),
+    };
+    if output.output_file.as_deref() == Some(STDIO) && format == Format::Json {
+        let message = "cannot write both the output and the report to stdout";
+        bail!(InvalidArgument(message.into()));
+    }
+    if profile_out.as_deref() == Some(STDIO) && format == Format::Json {
+        let message = "cannot write both the profile and the report to stdout";
+        bail!(InvalidArgument(message.into()));
+    }
+
+    let profile_format = profile.then_some(profile_args.profile_format);
+    let prints_profile = profile && profile_out.as_deref().is_none_or(|path| path == STDIO);
+    let report = match command {
+        Command::Run { args, coverage, .. } => {
+            if coverage.as_deref() == Some(STDIO) && format == Format::Json {
+                let message = "cannot write both the coverage and the report to stdout";
+                bail!(InvalidArgument(message.into()));
+            }
+            let profile_args = profile.then_some(profile_args);
+            Report::Run(triton_cli::run(args, profile_args, coverage.as_deref())?)
+        }
+        Command::Prove {
+            args,
+            stark,
+            artifacts,
+        } => {
+            if artifacts.writes_stdout() && (prints_profile || format == Format::Json) {
+                let message = "cannot write both an artifact and the report to stdout";
+                bail!(InvalidArgument(message.into()));
+            }
+            Report::Prove(triton_cli::prove(args, stark, artifacts, profile_format)?)
+        }
+        Command::Verify {
+            artifacts,
+            expectations,
+            batch: None,
+        } => Report::Verify(triton_cli::verify(artifacts, expectations, profile_format)?),
+        Command::Verify {
+            expectations,
+            batch: Some(batch),
+            ..
+        } => {
+            if profile {
+                let message = "profiling is not supported for batch verification";
+                bail!(InvalidArgument(message.into()));
+            }
+            Report::VerifyBatch(triton_cli::verify_batch(&batch, expectations)?)
+        }
+        Command::Estimate { args, stark } => {
+            if profile {
+                let message = "profiling is not supported for estimating";
+                bail!(InvalidArgument(message.into()));
+            }
+            Report::Estimate(triton_cli::estimate(args, stark)?)
+        }
+        Command::Bench(args) => {
+            if profile {
+                let message = "profiling is not supported for benchmarking";
+                bail!(InvalidArgument(message.into()));
+            }
+            let mut num_printed_results = 0;
+            let print_result = |result: &BenchResult| {
+                if format == Format::Json {
+                    return;
+                }
+                if num_printed_results == 0 {
+                    println!("{}", BenchResult::HEADER);
+                }
+                println!("{result}");
+                num_printed_results += 1;
+            };
+            Report::Bench(triton_cli::bench(args, print_result)?)
+        }
+        Command::Trace { args, trace } => {
+            if profile {
+                let message = "profiling is not supported for tracing";
+                bail!(InvalidArgument(message.into()));
+            }
+            let trace_file = trace.trace_file.as_deref().unwrap_or(STDIO);
+            if trace_file == STDIO && format == Format::Json {
+                let message = "cannot write both the trace and the report to stdout";
+                bail!(InvalidArgument(message.into()));
+            }
+            let create_writer = || args::create(trace_file);
+            Report::Trace(triton_cli::trace(args, trace.trace_format, create_writer)?)
+        }
+        Command::Aet {
+            args,
+            aet_dir,
+            aet_format,
+        } => Report::Aet(triton_cli::export_aet(args, &aet_dir, aet_format)?),
+        Command::Profile(ProfileCommand::Diff(args)) => {
+            if profile {
+                let message = "profiling is not supported for comparing profiles";
+                bail!(InvalidArgument(message.into()));
+            }
+            Report::ProfileDiff(triton_cli::profile_diff(args)?)
+        }
+        Command::Debug(args) => return debug(args),
+        Command::Inspect(artifacts) => Report::Inspect(triton_cli::inspect(artifacts)?),
+        Command::Digest { program } => Report::Digest(triton_cli::digest(program)?),
+    };
+
+    // Everything that can fail happens before the report is printed. Otherwise,
+    // the report would be followed by an error.
+    let formatted_output = match &report {
+        Report::Run(report) => Some(triton_cli::output::format(
+            &report.output,
+            output.output_format,
+        )?),
+        _ => None,
+    };
+    if let Some(path) = &output.output_file
+        && let Some(formatted) = &formatted_output
+    {
+        let mut formatted = formatted.clone();
+        if !formatted.is_empty() {
+            formatted.push('\n');
+        }
+        args::write_bytes(path, formatted.as_bytes())?;
+    }
+    if let Some(path) = &profile_out
+        && let Some(profile) = report.profile()
+    {
+        let profile = format!("{}\n", profile::strip_colors(&profile.to_string()));
+        args::write_bytes(path, profile.as_bytes())?;
     }
 
-    artifacts.write(&claim, &proof)?;
+    let print_profile = profile_out.is_none();
+    let printed_output = formatted_output.filter(|_| output.output_file.is_none());
+    match format {
+        Format::Human => print_human_readable(&report, printed_output.as_deref(), print_profile),
+        Format::Json => report.print()?,
+    }
 
-    Ok(SUCCESS)
+    let exit_code = match report {
+        Report::Verify(report) if !report.verdict => exit_code::VERIFICATION_REJECTED,
+        Report::VerifyBatch(report) => batch_exit_code(&report),
+        Report::Bench(BenchReport {
+            error: Some(error), ..
+        }) => error.kind.exit_code(),
+        Report::ProfileDiff(report) if !report.regressions.is_empty() => {
+            exit_code::PROFILE_REGRESSED
+        }
+        _ => exit_code::SUCCESS,
+    };
+    Ok(ExitCode::from(exit_code))
 }
 
-fn verify(flags: Flags, artifacts: ProofArtifacts) -> Result<ExitCode> {
-    let (claim, proof) = artifacts.read()?;
+/// The exit code of the first pair that did not verify, if any.
+fn batch_exit_code(report: &VerifyBatchReport) -> u8 {
+    let Some(pair) = report.pairs.iter().find(|pair| !pair.verdict) else {
+        return exit_code::SUCCESS;
+    };
 
-    triton_vm::profiler::start("Triton VM – Verify");
-    let verdict = triton_vm::verify(Stark::default(), &claim, &proof);
-    if flags.profile {
-        let padded_height = proof.padded_height()?;
-        let profile = triton_vm::profiler::finish()
-            .with_padded_height(padded_height)
-            .with_fri_domain_len(fri_domain_length(padded_height)?);
+    match &pair.error {
+        Some(error) => error.kind.exit_code(),
+        None => exit_code::VERIFICATION_REJECTED,
+    }
+}
+
+/// Print the report for humans. The `output` of command “run” is given already
+/// formatted, and only if it is to be printed.
+fn print_human_readable(report: &Report, output: Option<&str>, print_profile: bool) {
+    if print_profile && let Some(profile) = report.profile() {
         println!("{profile}");
+        if let Report::Run(_) = report {
+            println!();
+        }
     }
 
-    let exit_code = if verdict { SUCCESS } else { FAILURE };
-    Ok(exit_code)
+    match report {
+        Report::Run(_) => {
+            if let Some(output) = output.filter(|output| !output.is_empty()) {
+                println!("{output}");
+            }
+        }
+        Report::Prove(_) | Report::Verify(_) => (),
+        Report::VerifyBatch(report) => {
+            for pair in &report.pairs {
+                match &pair.error {
+                    Some(error) => println!("{}: error: {}", pair.proof, error.message),
+                    None if pair.verdict => println!("{}: verified", pair.proof),
+                    None => println!("{}: failed", pair.proof),
+                }
+            }
+            let num_verified = report.num_verified;
+            let num_pairs = report.num_pairs;
+            println!("{num_verified} of {num_pairs} pairs verified");
+        }
+        Report::Estimate(report) => println!("{report}"),
+        Report::Bench(report) => {
+            // the results are printed while benchmarking
+            if let Some(stop) = &report.stop {
+                println!("{stop}");
+            }
+            if let Some(error) = &report.error {
+                eprintln!("Error: {}", error.message);
+            }
+        }
+        Report::Trace(_) => (),
+        Report::Aet(report) => println!("{report}"),
+        Report::ProfileDiff(report) => println!("{report}"),
+        Report::Inspect(report) => println!("{report}"),
+        Report::Digest(report) => println!("{:x}", report.program_digest),
+    }
 }
 
-fn fri_domain_length(padded_height: usize) -> Result<usize> {
-    let fri = Stark::default().fri(padded_height)?;
-    Ok(fri.domain.len())
+fn debug(args: RunArgs) -> Result<ExitCode> {
+    if args.reads_stdin() {
+        let message = "command “debug” reads its commands from stdin";
+        bail!(InvalidArgument(message.into()));
+    }
+
+    let limits = args.limits;
+    let dump_path = args.dump_state_on_error.clone();
+    let (state, initial_input) = args.parse_state_with_initial_input()?;
+    let state_dump = dump_path.map(|path| StateDump {
+        path,
+        initial_input,
+    });
+    let debugger = Debugger::new(state)
+        .with_limits(limits)
+        .with_state_dump(state_dump);
+
+    let stdin = std::io::stdin();
+    let prompt = stdin.is_terminal();
+    let end = debugger.session(stdin.lock(), std::io::stdout().lock(), prompt)?;
+
+    match end {
+        SessionEnd::Crashed(_) => Ok(ExitCode::from(exit_code::VM)),
+        SessionEnd::Halted | SessionEnd::Paused => Ok(ExitCode::SUCCESS),
+    }
 }
 
 #[cfg(test)]
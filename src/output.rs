@@ -0,0 +1,51 @@
+use anyhow::Result;
+use anyhow::bail;
+use itertools::Itertools;
+use triton_vm::prelude::BFieldElement;
+use triton_vm::prelude::Digest;
+use triton_vm::twenty_first::math::x_field_element::EXTENSION_DEGREE;
+
+use crate::args::InvalidArgument;
+use crate::args::OutputFormat;
+
+/// Render the public output of a program in the given format. The result does
+/// not end in a newline.
+pub fn format(output: &[BFieldElement], format: OutputFormat) -> Result<String> {
+    let formatted = match format {
+        OutputFormat::Decimal => output.iter().join(", "),
+        OutputFormat::Lines => output.iter().join("\n"),
+        OutputFormat::Hex => output
+            .iter()
+            .map(|element| format!("{:#x}", element.value()))
+            .join("\n"),
+        OutputFormat::Json => {
+            let values = output.iter().map(|element| element.value()).collect_vec();
+            serde_json::to_string(&values)?
+        }
+        OutputFormat::Xfe => groups::<EXTENSION_DEGREE>(output, "extension field elements")?
+            .map(|[c0, c1, c2]| format!("({c0}, {c1}, {c2})"))
+            .join("\n"),
+        OutputFormat::Digest => groups::<{ Digest::LEN }>(output, "digests")?
+            .map(|elements| format!("{:x}", Digest::new(elements)))
+            .join("\n"),
+    };
+
+    Ok(formatted)
+}
+
+fn groups<'o, const N: usize>(
+    output: &'o [BFieldElement],
+    what: &str,
+) -> Result<impl Iterator<Item = [BFieldElement; N]> + 'o> {
+    if !output.len().is_multiple_of(N) {
+        let message = format!(
+            "the output's {} elements can't be grouped into {what} of {N} elements",
+            output.len(),
+        );
+        bail!(InvalidArgument(message));
+    }
+
+    Ok(output
+        .chunks_exact(N)
+        .map(|chunk| <[_; N]>::try_from(chunk).unwrap()))
+}
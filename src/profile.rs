@@ -0,0 +1,425 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use anyhow::Result;
+use anyhow::bail;
+use serde::Deserialize;
+use serde::Serialize;
+use triton_vm::execution_trace_profiler::ExecutionTraceProfile;
+use triton_vm::execution_trace_profiler::VMTableHeights;
+use triton_vm::profiler::VMPerformanceProfile;
+
+use crate::args::InvalidArgument;
+use crate::args::ProfileArgs;
+use crate::args::ProfileFormat;
+use crate::args::ProfileTable;
+use crate::flamegraph;
+
+/// A profile in the requested [format](ProfileFormat).
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Profile {
+    /// The profile as rendered by Triton VM. Might contain color codes.
+    Human(String),
+    Execution(ExecutionProfile),
+    Performance(PerformanceProfile),
+
+    /// The call stacks of an execution profile, one per line.
+    Folded(String),
+
+    /// The SVG image of a flamegraph of an execution profile.
+    Flamegraph(String),
+}
+
+/// The profile of a program's execution: how much each subroutine contributes
+/// to the heights of Triton VM's tables.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionProfile {
+    pub cycle_count: u32,
+    pub padded_height: usize,
+
+    /// The heights of all tables, before padding.
+    pub table_heights: TableHeights,
+
+    /// Like in the human-readable profile, calls to the same subroutine at the
+    /// same call depth are combined.
+    pub subroutines: Vec<SubroutineProfile>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SubroutineProfile {
+    pub label: String,
+    pub call_depth: usize,
+    pub num_calls: usize,
+    pub cycle_count: u32,
+
+    /// How much the subroutine contributes to the height of each table.
+    pub table_heights: TableHeights,
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct TableHeights {
+    pub program: u32,
+    pub processor: u32,
+    pub op_stack: u32,
+    pub ram: u32,
+    pub jump_stack: u32,
+    pub hash: u32,
+    pub cascade: u32,
+    pub lookup: u32,
+    pub u32: u32,
+}
+
+/// The profile of Triton VM's prover or verifier: how long each step takes, and
+/// how much memory it needs.
+///
+/// Triton VM renders this profile only in human-readable form, from which
+/// durations and memory sizes are recovered with the precision they are
+/// printed with.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceProfile {
+    pub name: String,
+
+    /// Measured by Triton CLI, not recovered from Triton VM's rendering.
+    pub duration_in_ns: u64,
+
+    /// The largest resident set size of the process. Unknown on some systems.
+    pub max_rss_in_bytes: Option<u64>,
+    pub cycle_count: Option<usize>,
+    pub padded_height: Option<usize>,
+    pub fri_domain_len: Option<usize>,
+    pub tasks: Vec<TaskProfile>,
+    pub categories: Vec<CategoryProfile>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TaskProfile {
+    pub name: String,
+
+    /// The number of ancestors of the task. Tasks are listed in the order they
+    /// were started, such that a task's ancestors precede it.
+    pub depth: usize,
+
+    /// The combined duration of all invocations.
+    pub duration_in_ns: u64,
+    pub num_invocations: u64,
+    pub category: Option<String>,
+
+    /// How much the resident set size grew, or shrunk, during the task.
+    /// Unknown on some systems.
+    pub rss_change_in_bytes: Option<i64>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CategoryProfile {
+    pub name: String,
+    pub duration_in_ns: u64,
+}
+
+impl Profile {
+    pub fn execution(profile: &ExecutionTraceProfile, args: ProfileArgs) -> Self {
+        let table = args.profile_table;
+        match args.profile_format {
+            ProfileFormat::Human => Self::Human(profile.to_string()),
+            ProfileFormat::Json => Self::Execution(ExecutionProfile::new(profile)),
+            ProfileFormat::Folded => Self::Folded(flamegraph::folded(profile, table)),
+            ProfileFormat::Flamegraph => Self::Flamegraph(flamegraph::svg(profile, table)),
+        }
+    }
+
+    pub fn performance(profile: &VMPerformanceProfile, format: ProfileFormat) -> Result<Self> {
+        ensure_performance_profile_format(format)?;
+        let rendered = profile.to_string();
+        let profile = match format {
+            ProfileFormat::Json => Self::Performance(PerformanceProfile::parse(&rendered)?),
+            _ => Self::Human(rendered),
+        };
+
+        Ok(profile)
+    }
+}
+
+/// Only execution profiles have call stacks, which folded stacks and
+/// flamegraphs are made of.
+pub fn ensure_performance_profile_format(format: ProfileFormat) -> Result<()> {
+    if let ProfileFormat::Folded | ProfileFormat::Flamegraph = format {
+        let message = "only execution profiles can be rendered as folded stacks or flamegraphs";
+        bail!(InvalidArgument(message.into()));
+    }
+
+    Ok(())
+}
+
+/// Human-readable profiles are printed as they are, json profiles as pretty
+/// json.
+impl Display for Profile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let json = match self {
+            Self::Human(profile) | Self::Folded(profile) | Self::Flamegraph(profile) => {
+                return write!(f, "{profile}");
+            }
+            Self::Execution(profile) => serde_json::to_string_pretty(profile),
+            Self::Performance(profile) => serde_json::to_string_pretty(profile),
+        };
+
+        write!(f, "{}", json.map_err(|_| std::fmt::Error)?)
+    }
+}
+
+impl ExecutionProfile {
+    pub fn new(profile: &ExecutionTraceProfile) -> Self {
+        let mut subroutines: Vec<SubroutineProfile> = vec![];
+        for line in &profile.profile {
+            let contribution = line.table_heights_stop - line.table_heights_start;
+            let table_heights = TableHeights::from(contribution);
+            let same_subroutine = |s: &&mut SubroutineProfile| {
+                s.label == line.label && s.call_depth == line.call_depth
+            };
+            if let Some(subroutine) = subroutines.iter_mut().find(same_subroutine) {
+                subroutine.num_calls += 1;
+                subroutine.cycle_count += table_heights.processor;
+                subroutine.table_heights += table_heights;
+                continue;
+            }
+            subroutines.push(SubroutineProfile {
+                label: line.label.clone(),
+                call_depth: line.call_depth,
+                num_calls: 1,
+                cycle_count: table_heights.processor,
+                table_heights,
+            });
+        }
+
+        Self {
+            cycle_count: profile.total.processor,
+            padded_height: profile.padded_height,
+            table_heights: profile.total.into(),
+            subroutines,
+        }
+    }
+}
+
+impl From<VMTableHeights> for TableHeights {
+    fn from(heights: VMTableHeights) -> Self {
+        Self {
+            program: heights.program,
+            processor: heights.processor,
+            op_stack: heights.op_stack,
+            ram: heights.ram,
+            jump_stack: heights.jump_stack,
+            hash: heights.hash,
+            cascade: heights.cascade,
+            lookup: heights.lookup,
+            u32: heights.u32,
+        }
+    }
+}
+
+impl TableHeights {
+    /// Every table's height, together with the table's name.
+    pub fn named_heights(&self) -> [(&'static str, u32); 9] {
+        [
+            ("program", self.program),
+            ("processor", self.processor),
+            ("op_stack", self.op_stack),
+            ("ram", self.ram),
+            ("jump_stack", self.jump_stack),
+            ("hash", self.hash),
+            ("cascade", self.cascade),
+            ("lookup", self.lookup),
+            ("u32", self.u32),
+        ]
+    }
+
+    pub fn height(&self, table: ProfileTable) -> u32 {
+        match table {
+            ProfileTable::Program => self.program,
+            ProfileTable::Processor => self.processor,
+            ProfileTable::OpStack => self.op_stack,
+            ProfileTable::Ram => self.ram,
+            ProfileTable::JumpStack => self.jump_stack,
+            ProfileTable::Hash => self.hash,
+            ProfileTable::Cascade => self.cascade,
+            ProfileTable::Lookup => self.lookup,
+            ProfileTable::U32 => self.u32,
+        }
+    }
+}
+
+impl std::ops::AddAssign for TableHeights {
+    fn add_assign(&mut self, rhs: Self) {
+        self.program += rhs.program;
+        self.processor += rhs.processor;
+        self.op_stack += rhs.op_stack;
+        self.ram += rhs.ram;
+        self.jump_stack += rhs.jump_stack;
+        self.hash += rhs.hash;
+        self.cascade += rhs.cascade;
+        self.lookup += rhs.lookup;
+        self.u32 += rhs.u32;
+    }
+}
+
+impl PerformanceProfile {
+    /// Recover the profile from its rendering by Triton VM. Parts that can't be
+    /// recovered are left empty.
+    ///
+    /// The rendering starts with a title line, followed by one line per task,
+    /// like
+    ///
+    /// ```text
+    /// ├─Merkle tree     1.42s    1  21.98%  (hash – 52.57%)  +280.0 KiB
+    /// ```
+    ///
+    /// where the tree drawing to the left of the name encodes the task's
+    /// depth. After an empty line, the time spent in each category follows.
+    ///
+    /// The rendering is meant for humans and might change with any version of
+    /// Triton VM. Fails if neither a total duration nor any task can be
+    /// recovered, instead of silently producing an empty profile.
+    pub fn parse(rendered: &str) -> Result<Self> {
+        let rendered = strip_colors(rendered);
+        let mut lines = rendered.lines();
+
+        let title = lines.next().unwrap_or_default();
+        let title = title.strip_prefix("### ").unwrap_or(title);
+        let (name, title_rest) = title.split_once("   ").unwrap_or((title, ""));
+        let title_tokens = title_rest.split_whitespace().collect::<Vec<_>>();
+        let duration_in_ns = title_tokens.first().and_then(|t| parse_duration(t));
+        let max_rss_in_bytes = match title_tokens.as_slice() {
+            [.., size, unit] => parse_size(size, unit).and_then(|s| u64::try_from(s).ok()),
+            _ => None,
+        };
+
+        let tasks: Vec<_> = lines
+            .by_ref()
+            .take_while(|line| !line.trim().is_empty())
+            .filter_map(parse_task)
+            .collect();
+        let categories = lines
+            .skip_while(|line| !line.starts_with("### Categories"))
+            .skip(1)
+            .take_while(|line| !line.trim().is_empty())
+            .filter_map(parse_category)
+            .collect();
+
+        let Some(duration_in_ns) = duration_in_ns.filter(|&duration| duration > 0) else {
+            bail!("could not recover the total duration from Triton VM's performance profile");
+        };
+        if tasks.is_empty() {
+            bail!("could not recover any task from Triton VM's performance profile");
+        }
+
+        let profile = Self {
+            name: name.trim().to_string(),
+            duration_in_ns,
+            max_rss_in_bytes,
+            cycle_count: None,
+            padded_height: None,
+            fri_domain_len: None,
+            tasks,
+            categories,
+        };
+        Ok(profile)
+    }
+}
+
+fn parse_task(line: &str) -> Option<TaskProfile> {
+    let (tree, rest) = line.split_once('─')?;
+    let depth = tree.chars().count().div_ceil(2).checked_sub(1)?;
+    let (name, rest) = rest.split_once("   ")?;
+    let mut tokens = rest.split_whitespace();
+    let duration_in_ns = parse_duration(tokens.next()?)?;
+    let num_invocations = tokens.next()?.parse().ok()?;
+    let _share = tokens.next()?;
+
+    let mut tokens = tokens.peekable();
+    let mut category = None;
+    if let Some(name) = tokens.next_if(|token| token.starts_with('(')) {
+        category = Some(name.trim_start_matches('(').to_string());
+        for token in tokens.by_ref() {
+            if token.ends_with(')') {
+                break;
+            }
+        }
+    }
+    let rss_change_in_bytes = match (tokens.next(), tokens.next()) {
+        (Some(size), Some(unit)) => parse_size(size, unit),
+        _ => None,
+    };
+
+    let task = TaskProfile {
+        name: name.trim().to_string(),
+        depth,
+        duration_in_ns,
+        num_invocations,
+        category,
+        rss_change_in_bytes,
+    };
+    Some(task)
+}
+
+fn parse_category(line: &str) -> Option<CategoryProfile> {
+    let tokens = line.split_whitespace().collect::<Vec<_>>();
+    let [name @ .., duration, _share] = tokens.as_slice() else {
+        return None;
+    };
+    let category = CategoryProfile {
+        name: name.join(" "),
+        duration_in_ns: parse_duration(duration)?,
+    };
+    Some(category)
+}
+
+/// A duration as rendered by [`Duration`](std::time::Duration)'s `Debug`
+/// implementation, like “1.23ms”.
+fn parse_duration(duration: &str) -> Option<u64> {
+    let split = duration.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+    let (value, unit) = duration.split_at(split);
+    let value: f64 = value.parse().ok()?;
+    let factor = match unit {
+        "ns" => 1.0,
+        "µs" => 1e3,
+        "ms" => 1e6,
+        "s" => 1e9,
+        _ => return None,
+    };
+
+    // durations are at most a few days, far from the limits of u64
+    Some((value * factor).round() as u64)
+}
+
+/// A memory size like “+2.2 MiB”, with an optional sign.
+fn parse_size(size: &str, unit: &str) -> Option<i64> {
+    let (sign, size) = match size.chars().next()? {
+        '+' | '±' => (1.0, &size[size.chars().next()?.len_utf8()..]),
+        '-' => (-1.0, &size[1..]),
+        _ => (1.0, size),
+    };
+    let size: f64 = size.parse().ok()?;
+    let factor = match unit {
+        "B" => 1.0,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        "TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+
+    Some((sign * size * factor).round() as i64)
+}
+
+/// Remove the escape sequences that color text in a terminal.
+pub fn strip_colors(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|&c| c == 'm');
+            continue;
+        }
+        stripped.push(c);
+    }
+
+    stripped
+}
@@ -0,0 +1,331 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use serde::Serialize;
+
+use crate::args;
+use crate::args::InvalidArgument;
+use crate::args::ProfileDiffArgs;
+use crate::profile::ExecutionProfile;
+use crate::profile::PerformanceProfile;
+use crate::profile::Profile;
+
+/// How one metric changed from the old to the new profile.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct Change {
+    pub metric: String,
+
+    /// Absent if the profile does not know the metric, for example, because
+    /// the subroutine was not called.
+    pub old: Option<u64>,
+
+    /// Absent if the profile does not know the metric, for example, because
+    /// the subroutine is not called anymore.
+    pub new: Option<u64>,
+
+    /// Absent if either value is.
+    pub delta: Option<i128>,
+}
+
+/// The changes of a subroutine of an execution profile, or of a step of a
+/// performance profile.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct EntryChanges {
+    /// For steps of a performance profile, the names of all enclosing steps
+    /// come first, separated by slashes.
+    pub name: String,
+    pub depth: usize,
+    pub changes: Vec<Change>,
+}
+
+/// A total that increased by more than its threshold.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct Regression {
+    pub metric: String,
+    pub old: u64,
+    pub new: u64,
+    pub max_increase_in_percent: u64,
+}
+
+/// The changes between two profiles of the same kind.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Diff {
+    pub totals: Vec<Change>,
+    pub entries: Vec<EntryChanges>,
+}
+
+/// A subroutine or a step, with the values of all its metrics.
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Entry {
+    name: String,
+    depth: usize,
+    metrics: Vec<(String, u64)>,
+}
+
+/// Read a profile saved with `--profile-format json`, or a json report that
+/// includes such a profile.
+pub fn read(path: &str) -> Result<Profile> {
+    let not_a_profile = || format!("“{path}” is not a profile in json format");
+    let json = args::read_string(path)?;
+    let mut json: serde_json::Value = serde_json::from_str(&json).with_context(not_a_profile)?;
+    if json.get("command").is_some() {
+        json = json["profile"].take();
+    }
+    let profile = serde_json::from_value(json).with_context(not_a_profile)?;
+    if let Profile::Execution(_) | Profile::Performance(_) = profile {
+        return Ok(profile);
+    }
+
+    bail!(InvalidArgument(not_a_profile()))
+}
+
+pub fn diff(old: &Profile, new: &Profile) -> Result<Diff> {
+    let (old_totals, new_totals, old_entries, new_entries) = match (old, new) {
+        (Profile::Execution(old), Profile::Execution(new)) => (
+            execution_totals(old),
+            execution_totals(new),
+            subroutines(old),
+            subroutines(new),
+        ),
+        (Profile::Performance(old), Profile::Performance(new)) => (
+            performance_totals(old),
+            performance_totals(new),
+            tasks(old),
+            tasks(new),
+        ),
+        _ => {
+            let message = "can only compare two execution or two performance profiles";
+            bail!(InvalidArgument(message.into()));
+        }
+    };
+
+    let diff = Diff {
+        totals: changes(&old_totals, &new_totals),
+        entries: entry_changes(&old_entries, &new_entries),
+    };
+    Ok(diff)
+}
+
+fn execution_totals(profile: &ExecutionProfile) -> Vec<(String, u64)> {
+    let totals = [
+        ("cycle_count".to_string(), u64::from(profile.cycle_count)),
+        ("padded_height".to_string(), profile.padded_height as u64),
+    ];
+    let table_heights = profile
+        .table_heights
+        .named_heights()
+        .map(|(table, height)| (format!("{table}_table_height"), u64::from(height)));
+
+    totals.into_iter().chain(table_heights).collect()
+}
+
+fn subroutines(profile: &ExecutionProfile) -> Vec<Entry> {
+    profile
+        .subroutines
+        .iter()
+        .map(|subroutine| {
+            let counts = [
+                ("num_calls".to_string(), subroutine.num_calls as u64),
+                ("cycle_count".to_string(), u64::from(subroutine.cycle_count)),
+            ];
+            let table_heights = subroutine
+                .table_heights
+                .named_heights()
+                .map(|(table, height)| (format!("{table}_table_height"), u64::from(height)));
+
+            Entry {
+                name: subroutine.label.clone(),
+                depth: subroutine.call_depth,
+                metrics: counts.into_iter().chain(table_heights).collect(),
+            }
+        })
+        .collect()
+}
+
+fn performance_totals(profile: &PerformanceProfile) -> Vec<(String, u64)> {
+    let totals = [
+        ("duration_in_ns", Some(profile.duration_in_ns)),
+        ("max_rss_in_bytes", profile.max_rss_in_bytes),
+        ("cycle_count", profile.cycle_count.map(|c| c as u64)),
+        ("padded_height", profile.padded_height.map(|h| h as u64)),
+        ("fri_domain_len", profile.fri_domain_len.map(|l| l as u64)),
+    ];
+
+    totals
+        .into_iter()
+        .filter_map(|(metric, value)| Some((metric.to_string(), value?)))
+        .collect()
+}
+
+fn tasks(profile: &PerformanceProfile) -> Vec<Entry> {
+    let mut path: Vec<&str> = vec![];
+    let mut tasks: Vec<Entry> = vec![];
+    for task in &profile.tasks {
+        path.truncate(task.depth);
+        path.push(&task.name);
+        let entry = Entry {
+            name: path.join("/"),
+            depth: task.depth,
+            metrics: vec![
+                ("duration_in_ns".to_string(), task.duration_in_ns),
+                ("num_invocations".to_string(), task.num_invocations),
+            ],
+        };
+        tasks.push(entry);
+    }
+
+    tasks
+}
+
+/// The changes of all metrics either side knows, in the order of the new
+/// side, followed by those that only the old side knows.
+fn changes(old: &[(String, u64)], new: &[(String, u64)]) -> Vec<Change> {
+    let value = |metrics: &[(String, u64)], metric: &str| {
+        metrics
+            .iter()
+            .find(|(name, _)| name == metric)
+            .map(|&(_, value)| value)
+    };
+    let only_old = old
+        .iter()
+        .filter(|(metric, _)| value(new, metric).is_none());
+
+    new.iter()
+        .chain(only_old)
+        .map(|(metric, _)| {
+            let old = value(old, metric);
+            let new = value(new, metric);
+            Change {
+                metric: metric.clone(),
+                old,
+                new,
+                delta: old.zip(new).map(|(o, n)| i128::from(n) - i128::from(o)),
+            }
+        })
+        .collect()
+}
+
+/// Entries are matched by name and depth.
+fn entry_changes(old: &[Entry], new: &[Entry]) -> Vec<EntryChanges> {
+    let find = |entries: &[Entry], entry: &Entry| {
+        entries
+            .iter()
+            .find(|e| e.name == entry.name && e.depth == entry.depth)
+            .map(|e| e.metrics.clone())
+            .unwrap_or_default()
+    };
+    let only_old = old.iter().filter(|entry| {
+        !new.iter()
+            .any(|e| e.name == entry.name && e.depth == entry.depth)
+    });
+
+    new.iter()
+        .chain(only_old)
+        .map(|entry| EntryChanges {
+            name: entry.name.clone(),
+            depth: entry.depth,
+            changes: changes(&find(old, entry), &find(new, entry)),
+        })
+        .collect()
+}
+
+/// The totals that increased by more than the thresholds allow. Thresholds
+/// apply to totals only, not to individual subroutines or steps. A threshold
+/// for a total that is not known to both profiles is an error. This includes
+/// the height of any table if the table height threshold is set.
+pub fn regressions(totals: &[Change], args: &ProfileDiffArgs) -> Result<Vec<Regression>> {
+    let not_known = |metric: &str| {
+        let message = format!("the profiles don't both know the {metric}");
+        InvalidArgument(message)
+    };
+    let thresholds = [
+        (args.max_cycle_count_increase, "cycle_count"),
+        (args.max_padded_height_increase, "padded_height"),
+        (args.max_table_height_increase, "processor_table_height"),
+        (args.max_duration_increase, "duration_in_ns"),
+    ];
+    for (threshold, metric) in thresholds {
+        if threshold.is_some() && !totals.iter().any(|c| c.metric == metric) {
+            bail!(not_known(metric));
+        }
+    }
+
+    let mut regressions = vec![];
+    for change in totals {
+        let Some(max_increase_in_percent) = threshold(&change.metric, args) else {
+            continue;
+        };
+        let (Some(old), Some(new)) = (change.old, change.new) else {
+            bail!(not_known(&change.metric));
+        };
+        let max_new = u128::from(old) * (100 + u128::from(max_increase_in_percent));
+        if u128::from(new) * 100 > max_new {
+            regressions.push(Regression {
+                metric: change.metric.clone(),
+                old,
+                new,
+                max_increase_in_percent,
+            });
+        }
+    }
+
+    Ok(regressions)
+}
+
+fn threshold(metric: &str, args: &ProfileDiffArgs) -> Option<u64> {
+    match metric {
+        "cycle_count" => args.max_cycle_count_increase,
+        "padded_height" => args.max_padded_height_increase,
+        "duration_in_ns" => args.max_duration_increase,
+        m if m.ends_with("_table_height") => args.max_table_height_increase,
+        _ => None,
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let value = |v: Option<u64>| v.map_or_else(|| "–".to_string(), |v| v.to_string());
+        let old = value(self.old);
+        let new = value(self.new);
+        write!(f, "{:<26}{old:>14} → {new:>14}", self.metric)?;
+
+        let (Some(old), Some(delta)) = (self.old, self.delta) else {
+            return Ok(());
+        };
+        if delta == 0 {
+            return Ok(());
+        }
+        write!(f, "  {delta:+}")?;
+        if old > 0 {
+            let percent = 100.0 * delta as f64 / old as f64;
+            write!(f, " ({percent:+.2}%)")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Regression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            metric,
+            old,
+            new,
+            max_increase_in_percent,
+        } = self;
+        write!(
+            f,
+            "regression: {metric} increased from {old} to {new}, \
+             more than the acceptable {max_increase_in_percent}%"
+        )
+    }
+}
+
+impl Change {
+    pub fn is_unchanged(&self) -> bool {
+        self.delta == Some(0) || self.old.is_none() && self.new.is_none()
+    }
+}
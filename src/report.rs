@@ -0,0 +1,585 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use anyhow::Error;
+use itertools::Itertools;
+use serde::Serialize;
+use triton_vm::error::ProvingError;
+use triton_vm::prelude::BFieldElement;
+use triton_vm::prelude::Digest;
+use triton_vm::prelude::InstructionError;
+use triton_vm::prelude::Stark;
+use triton_vm::prelude::VMError;
+use triton_vm::twenty_first::error::ParseBFieldElementError;
+
+use crate::aet::TableFile;
+use crate::aet::TableHeight;
+use crate::args::ClaimMismatch;
+use crate::args::InsufficientSecurity;
+use crate::args::InvalidArgument;
+use crate::args::LimitExceeded;
+use crate::args::ProgramParseError;
+use crate::container::Header;
+use crate::container::MalformedProof;
+use crate::container::UnsupportedVersion;
+use crate::coverage::CoverageSummary;
+use crate::exit_code;
+use crate::input::MalformedInput;
+use crate::profile::Profile;
+use crate::profile_diff::Change;
+use crate::profile_diff::EntryChanges;
+use crate::profile_diff::Regression;
+
+/// A machine-readable summary of a command's result. Printed to stdout as a
+/// single json document if the [output format](crate::args::Format) is json.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Report {
+    Run(RunReport),
+    Prove(ProveReport),
+    Verify(VerifyReport),
+    VerifyBatch(VerifyBatchReport),
+    Estimate(EstimateReport),
+    Bench(BenchReport),
+    Trace(TraceReport),
+    Aet(AetReport),
+    ProfileDiff(ProfileDiffReport),
+    Inspect(InspectReport),
+    Digest(DigestReport),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct RunReport {
+    pub output: Vec<BFieldElement>,
+    pub cycle_count: u32,
+
+    /// Only known if the execution was profiled or its padded height limited.
+    pub padded_height: Option<usize>,
+    pub profile: Option<Profile>,
+
+    /// Present if coverage was recorded.
+    pub coverage: Option<CoverageSummary>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct EstimateReport {
+    pub stark: Stark,
+    pub cycle_count: usize,
+
+    /// The heights of all tables, before padding.
+    pub heights: Vec<TableHeight>,
+
+    /// The tallest table, which determines the padded height.
+    pub dominating_table: String,
+    pub padded_height: usize,
+    pub fri_domain_len: usize,
+
+    /// The expected size of the bincode-encoded proof. The actual size varies
+    /// slightly with the randomness of the prover.
+    pub proof_size_in_bytes: u64,
+
+    /// A rough estimate, assuming the prover caches the low-degree extended
+    /// trace. See [`estimate::peak_memory_in_bytes`](crate::estimate::peak_memory_in_bytes).
+    pub peak_memory_in_bytes: u64,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct BenchReport {
+    pub stark: Stark,
+
+    /// One result per padded height that was proven, in increasing order.
+    pub results: Vec<BenchResult>,
+
+    /// Present if the benchmark stopped before the largest padded height
+    /// because proving would need too much memory.
+    pub stop: Option<BenchStop>,
+
+    /// Present if proving failed for some padded height, which ends the
+    /// benchmark. The results up to that padded height are kept.
+    pub error: Option<ErrorSummary>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct BenchResult {
+    pub log2_padded_height: u32,
+    pub padded_height: usize,
+    pub cycle_count: usize,
+    pub fri_domain_len: usize,
+
+    /// Includes tracing the execution of the program.
+    pub duration_in_ns: u64,
+
+    /// Unknown on some platforms.
+    pub max_rss_in_bytes: Option<u64>,
+    pub estimated_peak_memory_in_bytes: u64,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize)]
+pub struct BenchStop {
+    /// The smallest padded height that was not proven, as log₂.
+    pub log2_padded_height: u32,
+    pub estimated_peak_memory_in_bytes: u64,
+    pub available_memory_in_bytes: u64,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct TraceReport {
+    pub output: Vec<BFieldElement>,
+    pub cycle_count: u32,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct AetReport {
+    pub dir: String,
+    pub files: Vec<TableFile>,
+
+    /// The heights of all tables, before padding.
+    pub heights: Vec<TableHeight>,
+    pub padded_height: usize,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct ProfileDiffReport {
+    pub old: String,
+    pub new: String,
+
+    /// Totals like the cycle count, the padded height, or the duration.
+    pub totals: Vec<Change>,
+
+    /// The subroutines of execution profiles, or the steps of performance
+    /// profiles, matched by name and depth.
+    pub entries: Vec<EntryChanges>,
+
+    /// Empty if no total increased by more than its threshold.
+    pub regressions: Vec<Regression>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct ProveReport {
+    pub claim: String,
+    pub proof: String,
+    pub stark: Stark,
+    pub output: Vec<BFieldElement>,
+    pub cycle_count: usize,
+    pub padded_height: usize,
+    pub profile: Option<Profile>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct VerifyReport {
+    /// Absent if no claim file was given, in which case the claim contained in
+    /// the proof was verified, or, for proofs in the legacy format, the claim
+    /// in the default claim file.
+    pub claim: Option<String>,
+    pub proof: String,
+    pub stark: Stark,
+    pub verdict: bool,
+
+    /// Unknown if the proof is malformed.
+    pub padded_height: Option<usize>,
+    pub profile: Option<Profile>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct VerifyBatchReport {
+    pub pairs: Vec<PairVerdict>,
+    pub num_pairs: usize,
+    pub num_verified: usize,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct PairVerdict {
+    /// Absent unless given in the manifest. Then, the claim contained in the
+    /// proof was verified, or, for proofs in the legacy format, the claim in the
+    /// claim file of the same name as the proof.
+    pub claim: Option<String>,
+    pub proof: String,
+    pub verdict: bool,
+
+    /// Present if the pair could not be verified at all, for example, because
+    /// a file is missing.
+    pub error: Option<ErrorSummary>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct InspectReport {
+    pub claim: ClaimSummary,
+
+    /// Absent if there is no proof file.
+    pub proof: Option<ProofSummary>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct ClaimSummary {
+    /// The version of the proof format the claim is about.
+    pub version: u32,
+    pub program_digest: Digest,
+    pub input_len: usize,
+    pub input: Vec<BFieldElement>,
+    pub output_len: usize,
+    pub output: Vec<BFieldElement>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct ProofSummary {
+    /// The size of the bincode-encoded proof.
+    pub size_in_bytes: u64,
+
+    /// Unknown if the proof is malformed.
+    pub padded_height: Option<usize>,
+
+    /// Unknown if the proof is malformed.
+    pub fri_domain_len: Option<usize>,
+    pub stark: Stark,
+
+    /// The versions recorded in the proof file. Absent for proofs in the
+    /// legacy format.
+    pub container: Option<Header>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize)]
+pub struct DigestReport {
+    pub program_digest: Digest,
+}
+
+/// The json document printed in place of a [`Report`] if the command failed.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct ErrorReport {
+    /// Absent if the arguments are too malformed to tell the command.
+    pub command: Option<String>,
+    pub error: ErrorSummary,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct ErrorSummary {
+    pub kind: ErrorKind,
+    pub message: String,
+
+    /// The kind of instruction error if Triton VM crashed, in snake case, like
+    /// “assertion_failed”.
+    pub instruction_error: Option<&'static str>,
+}
+
+/// A coarse classification of everything that can go wrong. Each kind has its
+/// own [exit code](ErrorKind::exit_code).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// Some argument is invalid, for example, because it contradicts another.
+    InvalidArgument,
+
+    /// A file could not be read or written.
+    Io,
+
+    /// The program could not be parsed.
+    Program,
+
+    /// The public input could not be parsed.
+    Input,
+
+    /// Some json or binary file could not be deserialized, or is of an
+    /// unsupported version.
+    Deserialization,
+
+    /// Triton VM crashed during execution of the program.
+    Vm,
+
+    /// Proof generation failed for a reason other than a crash of Triton VM.
+    Proving,
+
+    /// The program exceeded some limit, like the maximum number of cycles.
+    LimitExceeded,
+
+    /// The claim is not about what the verifier expects.
+    ClaimMismatch,
+
+    /// The STARK parameters of the proof provide less security than the
+    /// verifier expects.
+    InsufficientSecurity,
+
+    /// Anything else.
+    Other,
+}
+
+impl Report {
+    pub fn print(&self) -> anyhow::Result<()> {
+        println!("{}", serde_json::to_string(self)?);
+        Ok(())
+    }
+
+    /// The profile, if the command was profiled.
+    pub fn profile(&self) -> Option<&Profile> {
+        match self {
+            Self::Run(report) => report.profile.as_ref(),
+            Self::Prove(report) => report.profile.as_ref(),
+            Self::Verify(report) => report.profile.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl Display for InspectReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let unknown = || String::from("unknown");
+        let claim = &self.claim;
+        writeln!(f, "proof version:        {}", claim.version)?;
+        writeln!(f, "program digest:       {:x}", claim.program_digest)?;
+        writeln!(f, "input length:         {}", claim.input_len)?;
+        writeln!(f, "input:                {}", claim.input.iter().join(", "))?;
+        writeln!(f, "output length:        {}", claim.output_len)?;
+        write!(
+            f,
+            "output:               {}",
+            claim.output.iter().join(", ")
+        )?;
+
+        let Some(proof) = &self.proof else {
+            return Ok(());
+        };
+        let padded_height = proof.padded_height.map_or_else(unknown, |h| h.to_string());
+        let fri_domain_len = proof.fri_domain_len.map_or_else(unknown, |l| l.to_string());
+        let file_format = proof.container.map_or_else(
+            || String::from("legacy"),
+            |header| {
+                format!(
+                    "container version {}, proof version {}",
+                    header.format_version, header.proof_version
+                )
+            },
+        );
+        let stark = &proof.stark;
+        writeln!(f)?;
+        writeln!(f, "proof file format:    {file_format}")?;
+        writeln!(f, "proof size:           {} bytes", proof.size_in_bytes)?;
+        writeln!(f, "padded height:        {padded_height}")?;
+        writeln!(f, "FRI domain length:    {fri_domain_len}")?;
+        writeln!(f, "security level:       {}", stark.security_level)?;
+        writeln!(f, "FRI expansion factor: {}", stark.fri_expansion_factor)?;
+        writeln!(f, "trace randomizers:    {}", stark.num_trace_randomizers)?;
+        write!(f, "collinearity checks:  {}", stark.num_collinearity_checks)
+    }
+}
+
+impl Display for EstimateReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let dominating_height = self
+            .heights
+            .iter()
+            .find(|h| h.table == self.dominating_table)
+            .map_or(0, |h| h.height);
+        let mib = |bytes: u64| bytes as f64 / f64::from(1 << 20);
+        let proof_size = self.proof_size_in_bytes;
+        let peak_memory = self.peak_memory_in_bytes;
+        writeln!(f, "cycle count:          {}", self.cycle_count)?;
+        writeln!(f, "padded height:        {}", self.padded_height)?;
+        writeln!(
+            f,
+            "dominating table:     {} ({dominating_height} rows)",
+            self.dominating_table
+        )?;
+        writeln!(f, "FRI domain length:    {}", self.fri_domain_len)?;
+        writeln!(
+            f,
+            "proof size:           ~{proof_size} bytes ({:.2} MiB)",
+            mib(proof_size)
+        )?;
+        write!(
+            f,
+            "peak memory:          ~{peak_memory} bytes ({:.2} MiB)",
+            mib(peak_memory)
+        )
+    }
+}
+
+impl BenchResult {
+    /// The header of the table the results are rows of.
+    pub const HEADER: &str = "log₂ height   cycle count   FRI domain length   proving time   \
+                              max RSS   estimated memory";
+}
+
+/// A row of the table headed by [`BenchResult::HEADER`].
+impl Display for BenchResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let seconds = self.duration_in_ns as f64 / 1e9;
+        let max_rss = self
+            .max_rss_in_bytes
+            .map_or_else(|| String::from("unknown"), mebibytes);
+        write!(
+            f,
+            "{:>11}   {:>11}   {:>17}   {:>12}   {max_rss:>7}   {:>16}",
+            self.log2_padded_height,
+            self.cycle_count,
+            self.fri_domain_len,
+            format!("{seconds:.2} s"),
+            mebibytes(self.estimated_peak_memory_in_bytes),
+        )
+    }
+}
+
+impl Display for BenchStop {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "stopped before padded height 2^{}: proving needs about {}, but only {} are available",
+            self.log2_padded_height,
+            mebibytes(self.estimated_peak_memory_in_bytes),
+            mebibytes(self.available_memory_in_bytes),
+        )
+    }
+}
+
+fn mebibytes(bytes: u64) -> String {
+    format!("{} MiB", bytes >> 20)
+}
+
+impl Display for AetReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for TableHeight { table, height } in &self.heights {
+            writeln!(f, "{:<15}{height}", format!("{table}:"))?;
+        }
+        write!(f, "padded height: {}", self.padded_height)
+    }
+}
+
+/// All totals, followed by the changed metrics of every subroutine or step that
+/// changed, followed by the regressions.
+impl Display for ProfileDiffReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.totals.iter().join("\n"))?;
+        for entry in &self.entries {
+            let changes = entry
+                .changes
+                .iter()
+                .filter(|c| !c.is_unchanged())
+                .collect_vec();
+            if changes.is_empty() {
+                continue;
+            }
+            write!(f, "\n\n{} (depth {})", entry.name, entry.depth)?;
+            for change in changes {
+                write!(f, "\n  {change}")?;
+            }
+        }
+        if !self.regressions.is_empty() {
+            write!(f, "\n\n{}", self.regressions.iter().join("\n"))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ErrorReport {
+    pub fn new(command: Option<&str>, error: &Error) -> Self {
+        let command = command.map(String::from);
+        let error = ErrorSummary::new(error);
+        Self { command, error }
+    }
+
+    pub fn print(&self) -> anyhow::Result<()> {
+        println!("{}", serde_json::to_string(self)?);
+        Ok(())
+    }
+}
+
+impl ErrorSummary {
+    pub fn new(error: &Error) -> Self {
+        let kind = ErrorKind::of(error);
+        let vm_error = error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<VMError>());
+        let message = match vm_error {
+            // the default message includes a large, human-oriented table
+            Some(vm_error) => vm_error.source.to_string(),
+            None => format!("{error:#}"),
+        };
+        let instruction_error = vm_error.map(|vm_error| instruction_error_name(&vm_error.source));
+
+        Self {
+            kind,
+            message,
+            instruction_error,
+        }
+    }
+}
+
+fn instruction_error_name(error: &InstructionError) -> &'static str {
+    match error {
+        InstructionError::InvalidOpcode(_) => "invalid_opcode",
+        InstructionError::OutOfRangeOpcode(_) => "out_of_range_opcode",
+        InstructionError::IllegalArgument(_, _) => "illegal_argument",
+        InstructionError::InstructionPointerOverflow => "instruction_pointer_overflow",
+        InstructionError::JumpStackIsEmpty => "jump_stack_is_empty",
+        InstructionError::AssertionFailed(_) => "assertion_failed",
+        InstructionError::VectorAssertionFailed(_, _) => "vector_assertion_failed",
+        InstructionError::InverseOfZero => "inverse_of_zero",
+        InstructionError::DivisionByZero => "division_by_zero",
+        InstructionError::SpongeNotInitialized => "sponge_not_initialized",
+        InstructionError::LogarithmOfZero => "logarithm_of_zero",
+        InstructionError::EmptyPublicInput(_) => "empty_public_input",
+        InstructionError::EmptySecretInput(_) => "empty_secret_input",
+        InstructionError::EmptySecretDigestInput => "empty_secret_digest_input",
+        InstructionError::MachineHalted => "machine_halted",
+        InstructionError::OpStackError(_) => "op_stack_error",
+        _ => "other",
+    }
+}
+
+impl ErrorKind {
+    pub fn of(error: &Error) -> Self {
+        for cause in error.chain() {
+            if cause.is::<VMError>() {
+                return Self::Vm;
+            }
+            if cause.is::<LimitExceeded>() {
+                return Self::LimitExceeded;
+            }
+            if cause.is::<ClaimMismatch>() {
+                return Self::ClaimMismatch;
+            }
+            if cause.is::<InsufficientSecurity>() {
+                return Self::InsufficientSecurity;
+            }
+            if cause.is::<InvalidArgument>() {
+                return Self::InvalidArgument;
+            }
+            if cause.is::<ProvingError>() {
+                return Self::Proving;
+            }
+            if cause.is::<ProgramParseError>() {
+                return Self::Program;
+            }
+            if cause.is::<ParseBFieldElementError>() || cause.is::<MalformedInput>() {
+                return Self::Input;
+            }
+            if cause.is::<serde_json::Error>()
+                || cause.is::<bincode::Error>()
+                || cause.is::<MalformedProof>()
+                || cause.is::<UnsupportedVersion>()
+            {
+                return Self::Deserialization;
+            }
+            if cause.is::<std::io::Error>() {
+                return Self::Io;
+            }
+        }
+
+        Self::Other
+    }
+
+    /// The exit code of Triton CLI if a command fails with this kind of error.
+    pub const fn exit_code(self) -> u8 {
+        match self {
+            Self::InvalidArgument => exit_code::INVALID_ARGUMENT,
+            Self::Io => exit_code::IO,
+            Self::Program => exit_code::PROGRAM,
+            Self::Input => exit_code::INPUT,
+            Self::Deserialization => exit_code::DESERIALIZATION,
+            Self::Vm => exit_code::VM,
+            Self::Proving => exit_code::PROVING,
+            Self::LimitExceeded => exit_code::LIMIT_EXCEEDED,
+            Self::ClaimMismatch => exit_code::CLAIM_MISMATCH,
+            Self::InsufficientSecurity => exit_code::INSUFFICIENT_SECURITY,
+            Self::Other => exit_code::FAILURE,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::borrow::Cow;
+use std::io::Write;
+
+use anyhow::Result;
+use itertools::Itertools;
+use serde::Serialize;
+use strum::EnumCount;
+use triton_vm::isa::instruction::Instruction;
+use triton_vm::isa::op_stack::OpStackElement;
+use triton_vm::prelude::VMState;
+
+use crate::args::TraceFormat;
+
+/// The number of op stack elements in each row of the trace. These are the
+/// elements accessible to instructions.
+const NUM_OP_STACK_ELEMENTS: usize = OpStackElement::COUNT;
+
+/// The state of Triton VM right before the instruction of some cycle is
+/// executed.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct TraceRow {
+    pub cycle: u32,
+    pub instruction_pointer: usize,
+
+    /// The instruction about to be executed. Instruction `call` refers to its
+    /// target by label, which keeps traces of different versions of a program
+    /// comparable.
+    pub instruction: String,
+    pub jump_stack_depth: usize,
+    pub op_stack_depth: usize,
+
+    /// The top of the op stack in canonical representation, top element first.
+    pub op_stack: [u64; NUM_OP_STACK_ELEMENTS],
+}
+
+impl TraceRow {
+    pub fn new(state: &VMState) -> Self {
+        let instruction = match state.current_instruction() {
+            Ok(Instruction::Call(address)) => {
+                let label = state.program.label_for_address(address.value());
+                format!("call {label}")
+            }
+            Ok(instruction) => instruction.to_string(),
+            Err(err) => err.to_string(),
+        };
+
+        Self {
+            cycle: state.cycle_count,
+            instruction_pointer: state.instruction_pointer,
+            instruction,
+            jump_stack_depth: state.jump_stack.len(),
+            op_stack_depth: state.op_stack.len(),
+            op_stack: std::array::from_fn(|i| state.op_stack[i].value()),
+        }
+    }
+}
+
+/// Writes the rows of an execution trace as they are produced, one line per
+/// row.
+#[derive(Debug)]
+pub struct TraceWriter<W: Write> {
+    writer: W,
+    format: TraceFormat,
+}
+
+impl<W: Write> TraceWriter<W> {
+    /// In [CSV format](TraceFormat::Csv), this writes the header.
+    pub fn new(mut writer: W, format: TraceFormat) -> Result<Self> {
+        if format == TraceFormat::Csv {
+            let op_stack = (0..NUM_OP_STACK_ELEMENTS).map(|i| format!("st{i}"));
+            let header = [
+                "cycle",
+                "instruction_pointer",
+                "instruction",
+                "jump_stack_depth",
+                "op_stack_depth",
+            ]
+            .map(String::from)
+            .into_iter()
+            .chain(op_stack)
+            .join(",");
+            writeln!(writer, "{header}")?;
+        }
+
+        Ok(Self { writer, format })
+    }
+
+    pub fn write(&mut self, row: &TraceRow) -> Result<()> {
+        match self.format {
+            TraceFormat::Csv => writeln!(
+                self.writer,
+                "{},{},{},{},{},{}",
+                row.cycle,
+                row.instruction_pointer,
+                csv_field(&row.instruction),
+                row.jump_stack_depth,
+                row.op_stack_depth,
+                row.op_stack.iter().join(","),
+            )?,
+            TraceFormat::Jsonl => {
+                serde_json::to_writer(&mut self.writer, row)?;
+                writeln!(self.writer)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+/// Quotes the field if it contains a separator, a quote, or a line break, as
+/// described in RFC 4180.
+fn csv_field(field: &str) -> Cow<'_, str> {
+    if !field.contains([',', '"', '\n', '\r']) {
+        return Cow::Borrowed(field);
+    }
+
+    Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+}
@@ -1,13 +1,33 @@
+use std::collections::HashMap;
 use std::io::Write;
 use std::ops::Deref;
 use std::ops::DerefMut;
 
 use itertools::Itertools;
+use predicates::prelude::PredicateBooleanExt;
 use tempfile::NamedTempFile;
+use triton_cli::args::BenchArgs;
+use triton_cli::args::Expectations;
+use triton_cli::args::ExpectedClaim;
+use triton_cli::args::Flags;
+use triton_cli::args::InputArgs;
+use triton_cli::args::Limits;
+use triton_cli::args::ProgramParseError;
+use triton_cli::args::ProofArtifacts;
+use triton_cli::args::RunArgs;
+use triton_cli::args::StarkArgs;
+use triton_cli::profile::PerformanceProfile;
+use triton_cli::report::ErrorKind;
+use triton_vm::isa::instruction::Instruction;
 use triton_vm::prelude::BFieldElement;
+use triton_vm::prelude::Claim;
 use triton_vm::prelude::NonDeterminism;
+use triton_vm::prelude::Program;
 use triton_vm::prelude::PublicInput;
+use triton_vm::prelude::Stark;
+use triton_vm::prelude::VM;
 use triton_vm::prelude::VMState;
+use triton_vm::prelude::bfe;
 use triton_vm::prelude::bfe_vec;
 use triton_vm::prelude::triton_program;
 
@@ -78,10 +98,22 @@ fn run_without_arguments() {
     command()
         .arg("run")
         .assert()
-        .stderr(predicates::str::contains("argument"))
+        .stderr(predicates::str::starts_with("Error: either argument"))
         .failure();
 }
 
+#[test]
+fn defaults_of_arguments_match_the_command_line() {
+    fn parse<T: clap::Args + clap::FromArgMatches>() -> T {
+        let command = T::augment_args(clap::Command::new("triton-cli"));
+        T::from_arg_matches(&command.get_matches_from(["triton-cli"])).unwrap()
+    }
+
+    assert_eq!(BenchArgs::default(), parse::<BenchArgs>());
+    assert_eq!(Flags::default(), parse::<Flags>());
+    assert_eq!(StarkArgs::default(), parse::<StarkArgs>());
+}
+
 #[test]
 fn run_trivial_program() {
     let program = temp_file("halt");
@@ -264,6 +296,280 @@ fn prove_verify_and_profile_trivial_program() {
         .success();
 }
 
+#[test]
+fn run_and_profile_program_in_json_format() {
+    let program = temp_file(triton_program!(
+        call a call a halt
+        a: call b return
+        b: push 0 pop 1 return
+    ));
+    let program_path = program.path().to_str().unwrap();
+
+    let assert = command()
+        .args(["--profile", "--profile-format", "json", "run"])
+        .args(["--program", program_path])
+        .assert()
+        .stderr("")
+        .success();
+    let profile: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!(13, profile["cycle_count"]);
+    assert_eq!(256, profile["padded_height"]);
+    assert_eq!(13, profile["table_heights"]["processor"]);
+
+    let subroutines = profile["subroutines"].as_array().unwrap();
+    let labels = subroutines.iter().map(|s| &s["label"]).collect_vec();
+    assert_eq!(["a", "b"].as_slice(), labels);
+    assert_eq!(1, subroutines[1]["call_depth"]);
+    assert_eq!(2, subroutines[1]["num_calls"]);
+    assert_eq!(10, subroutines[0]["cycle_count"]);
+    assert_eq!(10, subroutines[0]["table_heights"]["jump_stack"]);
+
+    command()
+        .args(["--profile-format", "json", "run"])
+        .args(["--program", program_path])
+        .assert()
+        .stderr(predicates::str::contains("--profile"))
+        .failure();
+}
+
+#[test]
+fn run_and_profile_program_as_folded_stacks_and_flamegraph() {
+    let program = temp_file(triton_program!(
+        push 2 call foo call foo push 5 call bar halt
+        foo: push 3 mul call bar return
+        bar: push 1 add return
+    ));
+    let program_path = program.path().to_str().unwrap();
+
+    let folded = "program 6\nprogram;bar 3\nprogram;foo 8\nprogram;foo;bar 6\n";
+    command()
+        .args(["--profile", "--profile-format", "folded", "run"])
+        .args(["--program", program_path])
+        .assert()
+        .stdout(format!("{folded}\n"))
+        .stderr("")
+        .success();
+
+    let folded = "program 2\nprogram;bar 2\nprogram;foo 4\nprogram;foo;bar 4\n";
+    let dir = tempfile::tempdir().unwrap();
+    command_in_dir(&dir)
+        .args(["--profile", "--profile-format", "folded"])
+        .args([
+            "--profile-table",
+            "op-stack",
+            "--profile-out",
+            "stacks.folded",
+        ])
+        .args(["run", "--program", program_path])
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    let stacks = std::fs::read_to_string(dir.path().join("stacks.folded")).unwrap();
+    assert_eq!(folded, stacks);
+
+    command_in_dir(&dir)
+        .args(["--profile", "--profile-format", "flamegraph"])
+        .args(["--profile-out", "flamegraph.svg"])
+        .args(["run", "--program", program_path])
+        .assert()
+        .success();
+    let svg = std::fs::read_to_string(dir.path().join("flamegraph.svg")).unwrap();
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains("<title>program (23 cycles, 100.00%)</title>"));
+    assert!(svg.contains("<title>foo (14 cycles, 60.87%)</title>"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+
+    command()
+        .args(["--profile", "--profile-format", "flamegraph", "prove"])
+        .args(["--program", program_path])
+        .assert()
+        .stderr(predicates::str::contains("only execution profiles"))
+        .failure();
+}
+
+#[test]
+fn performance_profile_that_cannot_be_recovered_is_an_error() {
+    let title = "### Triton VM – Prove        6.08s    #Reps   Share  Category    30.6 MiB\n";
+    let task = "├─main tables            1.87s        1  30.70%              +10.6 MiB\n";
+    let profile = PerformanceProfile::parse(&format!("{title}{task}")).unwrap();
+    assert_eq!(6_080_000_000, profile.duration_in_ns);
+    assert_eq!("main tables", profile.tasks[0].name);
+
+    assert!(PerformanceProfile::parse("").is_err());
+    assert!(PerformanceProfile::parse(title).is_err());
+    assert!(PerformanceProfile::parse(&format!("### Triton VM – Prove\n{task}")).is_err());
+}
+
+#[test]
+fn prove_and_verify_with_json_profile_written_to_file() {
+    let program = temp_file("push 1 push 2 add write_io 1 halt");
+    let dir = tempfile::tempdir().unwrap();
+
+    command_in_dir(&dir)
+        .args(["--profile", "--profile-format", "json"])
+        .args(["--profile-out", "prove.json", "prove"])
+        .args(["--program", program.path().to_str().unwrap()])
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+    let profile = std::fs::read_to_string(dir.path().join("prove.json")).unwrap();
+    let profile: serde_json::Value = serde_json::from_str(&profile).unwrap();
+    assert_eq!("Triton VM – Prove", profile["name"]);
+    assert_eq!(5, profile["cycle_count"]);
+    assert_eq!(256, profile["padded_height"]);
+    assert!(profile["duration_in_ns"].as_u64().unwrap() > 0);
+
+    let tasks = profile["tasks"].as_array().unwrap();
+    let task = |name: &str| tasks.iter().find(|task| task["name"] == name).unwrap();
+    assert_eq!(0, task("main tables")["depth"]);
+    assert_eq!(1, task("pad")["depth"]);
+    assert_eq!("gen", task("pad")["category"]);
+    assert!(task("main tables")["duration_in_ns"].as_u64().unwrap() > 0);
+    assert!(!profile["categories"].as_array().unwrap().is_empty());
+
+    let assert = command_in_dir(&dir)
+        .args(["--format", "json", "--profile", "--profile-format", "json"])
+        .args(["--profile-out", "verify.json", "verify"])
+        .assert()
+        .stderr("")
+        .success();
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let profile = std::fs::read_to_string(dir.path().join("verify.json")).unwrap();
+    let profile: serde_json::Value = serde_json::from_str(&profile).unwrap();
+    assert_eq!("Triton VM – Verify", profile["name"]);
+    assert_eq!(report["profile"], profile);
+    assert!(!profile["tasks"].as_array().unwrap().is_empty());
+
+    command_in_dir(&dir)
+        .args([
+            "--format",
+            "json",
+            "--profile",
+            "--profile-out",
+            "-",
+            "verify",
+        ])
+        .assert()
+        .stdout(predicates::str::contains("invalid_argument"))
+        .failure();
+}
+
+#[test]
+fn compare_profiles_and_fail_on_regression() {
+    let old_program = temp_file(triton_program!(
+        push 2 call foo push 5 call bar halt
+        foo: push 3 mul call bar return
+        bar: push 1 add return
+    ));
+    let new_program = temp_file(triton_program!(
+        push 2 call foo call foo push 5 call bar halt
+        foo: push 3 mul call bar return
+        bar: push 1 add return
+    ));
+    let dir = tempfile::tempdir().unwrap();
+    command_in_dir(&dir)
+        .args([
+            "--profile",
+            "--profile-format",
+            "json",
+            "--profile-out",
+            "old.json",
+        ])
+        .args(["run", "--program", old_program.path().to_str().unwrap()])
+        .assert()
+        .success();
+    let new_report = command_in_dir(&dir)
+        .args(["--format", "json", "--profile", "--profile-format", "json"])
+        .args(["run", "--program", new_program.path().to_str().unwrap()])
+        .assert()
+        .success();
+    std::fs::write(dir.path().join("new.json"), &new_report.get_output().stdout).unwrap();
+
+    command_in_dir(&dir)
+        .args(["profile", "diff", "old.json", "new.json"])
+        .args(["--max-cycle-count-increase", "60"])
+        .args(["--max-padded-height-increase", "0"])
+        .assert()
+        .stdout(predicates::str::contains("cycle_count"))
+        .stdout(predicates::str::contains("+8 (+53.33%)"))
+        .stdout(predicates::str::contains("foo (depth 0)"))
+        .stdout(predicates::str::contains("regression").not())
+        .success();
+
+    let assert = command_in_dir(&dir)
+        .args([
+            "--format", "json", "profile", "diff", "old.json", "new.json",
+        ])
+        .args(["--max-cycle-count-increase", "50"])
+        .args(["--max-table-height-increase", "40"])
+        .assert()
+        .code(12);
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!("profile_diff", report["command"]);
+    assert_eq!("cycle_count", report["totals"][0]["metric"]);
+    assert_eq!(8, report["totals"][0]["delta"]);
+    let foo = report["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| entry["name"] == "foo")
+        .unwrap();
+    assert_eq!(1, foo["changes"][0]["old"]);
+    assert_eq!(2, foo["changes"][0]["new"]);
+    let regressions = report["regressions"].as_array().unwrap();
+    let metrics = regressions.iter().map(|r| &r["metric"]).collect_vec();
+    let expected = [
+        "cycle_count",
+        "program_table_height",
+        "processor_table_height",
+        "op_stack_table_height",
+        "jump_stack_table_height",
+        "hash_table_height",
+        "cascade_table_height",
+    ];
+    assert_eq!(expected.as_slice(), metrics);
+
+    command_in_dir(&dir)
+        .args(["profile", "diff", "old.json", "new.json"])
+        .args(["--max-duration-increase", "10"])
+        .assert()
+        .stderr(predicates::str::contains("duration"))
+        .code(2);
+
+    command_in_dir(&dir)
+        .args([
+            "--profile",
+            "--profile-format",
+            "json",
+            "--profile-out",
+            "prove.json",
+        ])
+        .args(["prove", "--program", old_program.path().to_str().unwrap()])
+        .assert()
+        .success();
+    command_in_dir(&dir)
+        .args(["profile", "diff", "prove.json", "prove.json"])
+        .args([
+            "--max-duration-increase",
+            "0",
+            "--max-padded-height-increase",
+            "0",
+        ])
+        .assert()
+        .stdout(predicates::str::contains("duration_in_ns"))
+        .stdout(predicates::str::contains("main tables (depth 0)").not())
+        .success();
+    command_in_dir(&dir)
+        .args(["profile", "diff", "old.json", "prove.json"])
+        .assert()
+        .stderr(predicates::str::contains(
+            "two execution or two performance",
+        ))
+        .code(2);
+}
+
 #[test]
 fn prove_verify_trivial_program_to_dedicated_files() {
     let program = temp_file("halt");
@@ -436,3 +742,1477 @@ fn verify_incorrect_proof() {
         .stderr("")
         .failure();
 }
+
+#[test]
+fn run_program_with_json_format() {
+    let program = temp_file("read_io 2 add write_io 1 halt");
+
+    let assert = command()
+        .args(["--format", "json", "run"])
+        .args(["--program", program.path().to_str().unwrap()])
+        .args(["--input", "42,58"])
+        .assert()
+        .stderr("")
+        .success();
+
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!("run", report["command"]);
+    assert_eq!(serde_json::json!([100]), report["output"]);
+    assert_eq!(4, report["cycle_count"]);
+}
+
+#[test]
+fn crash_with_json_format_reports_error_kind() {
+    let program = temp_file("push 0 assert halt");
+
+    let assert = command()
+        .args(["--format", "json", "run"])
+        .args(["--program", program.path().to_str().unwrap()])
+        .assert()
+        .failure();
+
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!("run", report["command"]);
+    assert_eq!("vm", report["error"]["kind"]);
+    assert_eq!("assertion_failed", report["error"]["instruction_error"]);
+}
+
+#[test]
+fn argument_errors_with_json_format_are_reported_as_json() {
+    let assert = command()
+        .args(["--format", "json", "run", "--bogus"])
+        .assert()
+        .stderr("")
+        .code(2);
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!("run", report["command"]);
+    assert_eq!("invalid_argument", report["error"]["kind"]);
+    assert_eq!(
+        "unexpected argument '--bogus' found",
+        report["error"]["message"]
+    );
+
+    let assert = command()
+        .args(["--format", "json", "verify", "--batch", "."])
+        .args(["--expect-input", "1", "--expect-input-file", "input.txt"])
+        .assert()
+        .code(2);
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!("verify_batch", report["command"]);
+    assert_eq!("invalid_argument", report["error"]["kind"]);
+
+    let assert = command()
+        .args(["--format", "json", "frobnicate"])
+        .assert()
+        .code(2);
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert!(report["command"].is_null());
+
+    command()
+        .args(["run", "--bogus"])
+        .assert()
+        .stdout("")
+        .stderr(predicates::str::contains("unexpected argument"))
+        .code(2);
+}
+
+#[test]
+fn prove_verify_program_with_json_format() {
+    let program = temp_file("read_io 1 write_io 1 halt");
+
+    let dir = tempfile::tempdir().unwrap();
+    let assert = command_in_dir(&dir)
+        .args(["--format", "json", "prove"])
+        .args(["--program", program.path().to_str().unwrap()])
+        .args(["--input", "7"])
+        .assert()
+        .success();
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!("prove", report["command"]);
+    assert_eq!("triton.claim", report["claim"]);
+    assert_eq!(serde_json::json!([7]), report["output"]);
+
+    let assert = command_in_dir(&dir)
+        .args(["--format", "json", "verify"])
+        .assert()
+        .success();
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!("verify", report["command"]);
+    assert_eq!(true, report["verdict"]);
+}
+
+#[test]
+fn debug_program_with_breakpoint_on_label() {
+    let program = temp_file(triton_program!(
+        push 3 call loop halt
+        loop:
+            dup 0 push 0 eq skiz return
+            dup 0 write_io 1 addi -1 recurse
+    ));
+
+    command()
+        .args(["debug", "--program", program.path().to_str().unwrap()])
+        .write_stdin("break loop\ncontinue\ncontinue\nstack\noutput\ndelete loop\ncontinue\n")
+        .assert()
+        .stdout(predicates::str::starts_with("[cycle 0] 0: push 3\n"))
+        .stdout(predicates::str::contains("[cycle 2] 5: dup 0\n"))
+        .stdout(predicates::str::contains("st0: 2\n"))
+        .stdout(predicates::str::contains("\n3\n"))
+        .stdout(predicates::str::ends_with("halted\n"))
+        .stderr("")
+        .success();
+}
+
+#[test]
+fn debug_program_reports_crash_and_unknown_commands() {
+    let program = temp_file("push 2 push 3 eq assert halt");
+
+    command()
+        .args(["debug", "--program", program.path().to_str().unwrap()])
+        .write_stdin("step 2\nbreak nowhere\nfrobnicate\ncontinue\nstep\n")
+        .assert()
+        .stdout(predicates::str::contains("[cycle 2] 4: eq\n"))
+        .stdout(predicates::str::contains("[cycle 3] crashed"))
+        .stdout(predicates::str::contains(
+            "error: unknown label “nowhere”\n",
+        ))
+        .stdout(predicates::str::contains(
+            "error: unknown command “frobnicate”",
+        ))
+        .stderr("")
+        .code(10);
+}
+
+#[test]
+fn run_and_prove_crashing_program_dump_state() {
+    let program = temp_file("push 5 read_io 1 push 2 push 3 eq assert halt");
+    let program_path = program.path().to_str().unwrap();
+
+    for the_command in ["run", "prove"] {
+        let dir = tempfile::tempdir().unwrap();
+        command_in_dir(&dir)
+            .args([the_command, "--program", program_path, "--input", "9"])
+            .args(["--dump-state-on-error", "crash.json"])
+            .assert()
+            .stderr(predicates::str::contains("assertion failed"))
+            .failure();
+
+        let dump = std::fs::read_to_string(dir.path().join("crash.json")).unwrap();
+        let state: VMState = serde_json::from_str(&dump).unwrap();
+        assert_eq!(5, state.cycle_count);
+        assert_eq!(9, state.instruction_pointer);
+        assert!(state.public_input.is_empty());
+
+        // replaying starts over with the recorded input
+        command_in_dir(&dir)
+            .args([the_command, "--initial-state", "crash.json"])
+            .assert()
+            .stderr(predicates::str::contains("assertion failed"))
+            .failure();
+    }
+}
+
+#[test]
+fn debug_dumped_state_resumes_at_failing_instruction() {
+    let program = temp_file("push 5 read_io 1 push 2 push 3 eq assert halt");
+
+    let dir = tempfile::tempdir().unwrap();
+    command_in_dir(&dir)
+        .args(["run", "--program", program.path().to_str().unwrap()])
+        .args(["--input", "9", "--dump-state-on-error", "crash.json"])
+        .assert()
+        .failure();
+
+    command_in_dir(&dir)
+        .args(["debug", "--initial-state", "crash.json"])
+        .write_stdin("stack\nstep\n")
+        .assert()
+        .stdout(predicates::str::starts_with(
+            "[cycle 5] 9: assert\nst0: 0\nst1: 9\n",
+        ))
+        .stdout(predicates::str::contains(
+            "[cycle 5] crashed: assertion failed",
+        ))
+        .code(10);
+}
+
+#[test]
+fn run_and_debug_non_terminating_program_with_cycle_limit() {
+    let program = temp_file("push 1 call loop halt loop: recurse");
+    let program_path = program.path().to_str().unwrap();
+
+    command()
+        .args(["run", "--program", program_path, "--max-cycles", "1000"])
+        .assert()
+        .stderr(predicates::str::contains(
+            "did not terminate within 1000 cycles",
+        ))
+        .code(3);
+
+    command()
+        .args(["debug", "--program", program_path, "--max-cycles", "10"])
+        .write_stdin("continue\n")
+        .assert()
+        .stdout(predicates::str::ends_with(
+            "[cycle 10] stopped: program did not terminate within 10 cycles\n",
+        ))
+        .success();
+}
+
+#[test]
+fn run_program_within_limits() {
+    let program = temp_file("read_io 2 add write_io 1 halt");
+
+    command()
+        .arg("run")
+        .args(["--program", program.path().to_str().unwrap()])
+        .args(["--input", "42,58"])
+        .args(["--max-cycles", "4", "--max-padded-height", "256"])
+        .assert()
+        .stdout("100\n")
+        .success();
+}
+
+#[test]
+fn prove_refuses_to_prove_if_padded_height_exceeds_limit() {
+    let program = temp_file("halt");
+
+    let dir = tempfile::tempdir().unwrap();
+    command_in_dir(&dir)
+        .args(["prove", "--program", program.path().to_str().unwrap()])
+        .args(["--max-padded-height", "128"])
+        .assert()
+        .stderr(predicates::str::contains(
+            "padded height exceeds the limit of 128",
+        ))
+        .code(3);
+    assert!(!dir.path().join("triton.proof").exists());
+}
+
+#[test]
+fn prove_verify_with_custom_stark_parameters() {
+    let program = temp_file("read_io 1 write_io 1 halt");
+
+    let dir = tempfile::tempdir().unwrap();
+    let assert = command_in_dir(&dir)
+        .args(["--format", "json", "prove"])
+        .args(["--program", program.path().to_str().unwrap()])
+        .args(["--input", "7"])
+        .args([
+            "--fri-expansion-factor",
+            "8",
+            "--num-collinearity-checks",
+            "20",
+        ])
+        .assert()
+        .success();
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!(60, report["stark"]["security_level"]);
+    assert_eq!(8, report["stark"]["fri_expansion_factor"]);
+    assert_eq!(20, report["stark"]["num_collinearity_checks"]);
+
+    // the parameters are recorded in the proof file, but are less secure than
+    // the default ones
+    command_in_dir(&dir).arg("verify").assert().code(13);
+    command_in_dir(&dir)
+        .args(["verify", "--min-security-level", "60"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn security_level_follows_from_number_of_collinearity_checks() {
+    let program = temp_file("halt");
+
+    command()
+        .args(["prove", "--program", program.path().to_str().unwrap()])
+        .args(["--security-level", "96", "--num-collinearity-checks", "20"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn verify_rejects_proof_with_weak_or_invalid_stark_parameters() {
+    let program = temp_file("halt");
+
+    let dir = tempfile::tempdir().unwrap();
+    command_in_dir(&dir)
+        .args(["prove", "--program", program.path().to_str().unwrap()])
+        .args(["--num-collinearity-checks", "1"])
+        .assert()
+        .success();
+    command_in_dir(&dir)
+        .args(["--format", "json", "verify"])
+        .assert()
+        .stdout(predicates::str::contains(
+            r#""kind":"insufficient_security""#,
+        ))
+        .stdout(predicates::str::contains("security level of 2 bits"))
+        .code(13);
+
+    // The STARK parameters follow the header: 8 magic bytes, 4 bytes format
+    // version, 4 bytes proof version. The FRI expansion factor is the second
+    // parameter.
+    let proof_path = dir.path().join("triton.proof");
+    let mut container = std::fs::read(&proof_path).unwrap();
+    let fri_expansion_factor = 8 + 4 + 4 + 8;
+    container[fri_expansion_factor..fri_expansion_factor + 8].copy_from_slice(&3_u64.to_le_bytes());
+    std::fs::write(&proof_path, container).unwrap();
+    command_in_dir(&dir)
+        .args(["verify", "--min-security-level", "1"])
+        .assert()
+        .stderr(predicates::str::contains("power of 2"))
+        .code(2);
+}
+
+#[test]
+fn verify_proof_container_without_claim_file() {
+    let program = temp_file("read_io 1 write_io 1 halt");
+
+    let dir = tempfile::tempdir().unwrap();
+    command_in_dir(&dir)
+        .args(["prove", "--program", program.path().to_str().unwrap()])
+        .args(["--input", "7"])
+        .assert()
+        .success();
+    std::fs::remove_file(dir.path().join("triton.claim")).unwrap();
+    command_in_dir(&dir).arg("verify").assert().success();
+}
+
+#[test]
+fn verify_proof_in_legacy_format() {
+    let program = triton_program!(read_io 1 write_io 1 halt);
+    let input = PublicInput::new(bfe_vec![7]);
+    let claim = Claim::about_program(&program).with_input(input.clone());
+    let (aet, output) = VM::trace_execution(program, input, NonDeterminism::default()).unwrap();
+    let claim = claim.with_output(output);
+    let stark = Stark::new(96, 3);
+    let proof = stark.prove(&claim, &aet).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let claim_file = serde_json::to_string(&claim).unwrap();
+    std::fs::write(dir.path().join("triton.claim"), claim_file).unwrap();
+    let proof = bincode::serialize(&proof).unwrap();
+    std::fs::write(dir.path().join("triton.proof"), proof).unwrap();
+
+    // without the parameters, the default parameters are used, which don't match
+    command_in_dir(&dir).arg("verify").assert().failure();
+
+    let parameters = serde_json::to_string(&stark).unwrap();
+    std::fs::write(dir.path().join("custom.json"), parameters).unwrap();
+    command_in_dir(&dir)
+        .args(["verify", "--parameters", "custom.json"])
+        .args(["--min-security-level", "96"])
+        .assert()
+        .success();
+
+    command_in_dir(&dir)
+        .args(["inspect", "--parameters", "custom.json"])
+        .assert()
+        .stdout(predicates::str::contains("proof file format:    legacy\n"))
+        .success();
+    let assert = command_in_dir(&dir)
+        .args(["--format", "json", "inspect", "--parameters", "custom.json"])
+        .assert()
+        .success();
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert!(report["proof"]["container"].is_null());
+
+    let other_version = Claim {
+        version: claim.version + 1,
+        ..claim
+    };
+    let other_version = serde_json::to_string(&other_version).unwrap();
+    std::fs::write(dir.path().join("triton.claim"), other_version).unwrap();
+    command_in_dir(&dir)
+        .args(["verify", "--parameters", "custom.json"])
+        .assert()
+        .stderr(predicates::str::contains(
+            "the claim is about proof version",
+        ))
+        .code(9);
+}
+
+#[test]
+fn verify_proof_container_of_other_versions_fails_with_diagnostic() {
+    let program = temp_file("halt");
+
+    let dir = tempfile::tempdir().unwrap();
+    command_in_dir(&dir)
+        .args(["prove", "--program", program.path().to_str().unwrap()])
+        .assert()
+        .success();
+    let proof_path = dir.path().join("triton.proof");
+    let container = std::fs::read(&proof_path).unwrap();
+
+    // Layout: 8 magic bytes, 4 bytes format version, 4 bytes proof version.
+    let mut other_format = container.clone();
+    other_format[8..12].copy_from_slice(&3_u32.to_le_bytes());
+    std::fs::write(&proof_path, other_format).unwrap();
+    command_in_dir(&dir)
+        .arg("verify")
+        .assert()
+        .stderr(predicates::str::contains(
+            "unsupported proof container format version 3",
+        ))
+        .code(9);
+
+    let proof_version = triton_vm::proof::CURRENT_VERSION;
+    assert_eq!(proof_version.to_le_bytes(), container[12..16]);
+    let mut other_proof_version = container;
+    other_proof_version[12..16].copy_from_slice(&(proof_version + 1).to_le_bytes());
+    std::fs::write(&proof_path, other_proof_version).unwrap();
+    command_in_dir(&dir)
+        .arg("verify")
+        .assert()
+        .stderr(predicates::str::contains(format!(
+            "proof was produced for proof version {}",
+            proof_version + 1
+        )))
+        .code(9);
+}
+
+#[test]
+fn prove_with_stark_parameters_from_file() {
+    let program = temp_file("halt");
+    let parameters = r#"{"security_level":64,"fri_expansion_factor":4,"num_trace_randomizers":50,"num_collinearity_checks":32}"#;
+    let parameters = temp_file(parameters);
+
+    let dir = tempfile::tempdir().unwrap();
+    command_in_dir(&dir)
+        .args(["prove", "--program", program.path().to_str().unwrap()])
+        .args(["--stark-parameters", parameters.path().to_str().unwrap()])
+        .assert()
+        .success();
+    command_in_dir(&dir)
+        .args(["verify", "--min-security-level", "64"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn prove_with_invalid_fri_expansion_factor_fails() {
+    let program = temp_file("halt");
+
+    command()
+        .args(["prove", "--program", program.path().to_str().unwrap()])
+        .args(["--fri-expansion-factor", "3"])
+        .assert()
+        .stderr(predicates::str::contains("power of 2"))
+        .failure();
+}
+
+#[test]
+fn inspect_claim_and_proof() {
+    let program = temp_file("read_io 2 add write_io 1 halt");
+
+    let dir = tempfile::tempdir().unwrap();
+    command_in_dir(&dir)
+        .args(["prove", "--program", program.path().to_str().unwrap()])
+        .args(["--input", "3,4"])
+        .assert()
+        .success();
+    command_in_dir(&dir)
+        .arg("inspect")
+        .assert()
+        .stdout(predicates::str::contains("input:                3, 4\n"))
+        .stdout(predicates::str::contains("output:               7\n"))
+        .stdout(predicates::str::contains("padded height:        256\n"))
+        .stdout(predicates::str::contains(format!(
+            "proof file format:    container version 2, proof version {}\n",
+            triton_vm::proof::CURRENT_VERSION
+        )))
+        .success();
+
+    let assert = command_in_dir(&dir)
+        .args(["--format", "json", "inspect"])
+        .assert()
+        .success();
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!("inspect", report["command"]);
+    assert_eq!(2, report["proof"]["container"]["format_version"]);
+    assert_eq!(
+        triton_vm::proof::CURRENT_VERSION,
+        report["proof"]["container"]["proof_version"]
+    );
+    assert_eq!(2, report["claim"]["input_len"]);
+    assert_eq!(serde_json::json!([7]), report["claim"]["output"]);
+    assert_eq!(256, report["proof"]["padded_height"]);
+    assert_eq!(2048, report["proof"]["fri_domain_len"]);
+    let proof_size = std::fs::metadata(dir.path().join("triton.proof"))
+        .unwrap()
+        .len();
+    assert!(report["proof"]["size_in_bytes"].as_u64().unwrap() < proof_size);
+}
+
+#[test]
+fn estimate_cost_of_proving_without_proving() {
+    let program =
+        temp_file("push 2 call foo push 5 push 3 lt pop 1 halt\nfoo: push 3 mul return\n");
+    let program_path = program.path().to_str().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    command_in_dir(&dir)
+        .args(["estimate", "--program", program_path])
+        .assert()
+        .stdout(predicates::str::contains("padded height:        256\n"))
+        .stdout(predicates::str::contains(
+            "dominating table:     Lookup (256 rows)\n",
+        ))
+        .stdout(predicates::str::contains("FRI domain length:    2048\n"))
+        .success();
+    assert!(!dir.path().join("triton.proof").exists());
+
+    let assert = command_in_dir(&dir)
+        .args(["--format", "json", "estimate", "--program", program_path])
+        .assert()
+        .success();
+    let estimate: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!("estimate", estimate["command"]);
+    assert_eq!(10, estimate["cycle_count"]);
+    assert!(estimate["peak_memory_in_bytes"].as_u64().unwrap() > 0);
+
+    command_in_dir(&dir)
+        .args(["prove", "--program", program_path])
+        .assert()
+        .success();
+    let assert = command_in_dir(&dir)
+        .args(["--format", "json", "inspect"])
+        .assert()
+        .success();
+    let inspect: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!(estimate["padded_height"], inspect["proof"]["padded_height"]);
+    assert_eq!(
+        estimate["fri_domain_len"],
+        inspect["proof"]["fri_domain_len"]
+    );
+    let estimated_size = estimate["proof_size_in_bytes"].as_u64().unwrap() as f64;
+    let actual_size = inspect["proof"]["size_in_bytes"].as_u64().unwrap() as f64;
+    assert!((estimated_size / actual_size - 1.0).abs() < 0.02);
+}
+
+#[test]
+fn bench_stops_before_running_out_of_memory() {
+    command()
+        .args(["bench", "--max-memory", "1"])
+        .assert()
+        .stdout("stopped before padded height 2^16: proving needs about 3954 MiB, but only 1 MiB are available\n")
+        .success();
+
+    let assert = command()
+        .args(["--format", "json", "bench", "--max-memory", "1"])
+        .args(["--min-log2-padded-height", "20"])
+        .assert()
+        .success();
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!("bench", report["command"]);
+    assert_eq!(serde_json::json!([]), report["results"]);
+    assert_eq!(20, report["stop"]["log2_padded_height"]);
+    assert_eq!(1 << 20, report["stop"]["available_memory_in_bytes"]);
+
+    command()
+        .args(["bench", "--min-log2-padded-height", "15"])
+        .assert()
+        .code(2);
+    command()
+        .args(["bench", "--min-log2-padded-height", "20"])
+        .args(["--max-log2-padded-height", "18"])
+        .assert()
+        .stderr(predicates::str::contains("must not exceed"))
+        .code(2);
+}
+
+#[test]
+fn bench_proves_a_program_for_every_padded_height() {
+    // like the built-in program, but small enough to be proven quickly
+    let spin = |crash_at: u32| {
+        let program = format!(
+            "read_io 1 dup 0 push {crash_at} eq push 0 eq assert\n\
+             addi -3 push 2 pow call spin halt\n\
+             spin:\n  dup 0 push 0 eq skiz return\n  addi -1 recurse\n"
+        );
+        triton_vm::prelude::Program::from_code(&program).unwrap()
+    };
+    let stark = Stark::default();
+    let max_memory = Some(1 << 44);
+
+    let mut progress = vec![];
+    let report = triton_cli::bench_program(&spin(0), stark, 9..=10, max_memory, |result| {
+        progress.push(result.clone());
+    })
+    .unwrap();
+    assert_eq!(None, report.stop);
+    assert_eq!(None, report.error);
+    assert_eq!(progress, report.results);
+    let padded_heights = report.results.iter().map(|r| r.padded_height).collect_vec();
+    assert_eq!(vec![512, 1024], padded_heights);
+    assert!(report.results.iter().all(|r| r.duration_in_ns > 0));
+
+    // a failure keeps the results up to that point
+    let report = triton_cli::bench_program(&spin(10), stark, 9..=10, max_memory, |_| ()).unwrap();
+    assert_eq!(1, report.results.len());
+    let error = report.error.unwrap();
+    assert_eq!(ErrorKind::Vm, error.kind);
+    assert_eq!(Some("assertion_failed"), error.instruction_error);
+}
+
+#[test]
+fn inspect_claim_without_proof() {
+    let program = triton_program!(halt);
+    let claim = Claim::about_program(&program).with_input(bfe_vec![1, 2]);
+    let claim_file = temp_file(serde_json::to_string(&claim).unwrap());
+
+    let assert = command()
+        .args(["--format", "json", "inspect"])
+        .args(["--claim", claim_file.path().to_str().unwrap()])
+        .assert()
+        .success();
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!(
+        program.hash().to_hex(),
+        report["claim"]["program_digest"].as_str().unwrap()
+    );
+    assert_eq!(serde_json::json!([1, 2]), report["claim"]["input"]);
+    assert!(report["proof"].is_null());
+}
+
+#[test]
+fn digest_of_program_matches_claim() {
+    let program = triton_program!(read_io 1 write_io 1 halt);
+    let program_file = temp_file(&program);
+
+    command()
+        .args(["digest", "--program", program_file.path().to_str().unwrap()])
+        .assert()
+        .stdout(format!("{}\n", program.hash().to_hex()))
+        .success();
+
+    let claim = Claim::about_program(&program);
+    let assert = command()
+        .args(["--format", "json", "digest"])
+        .args(["--program", program_file.path().to_str().unwrap()])
+        .assert()
+        .success();
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!(claim.program_digest.to_hex(), report["program_digest"]);
+}
+
+#[test]
+fn verify_with_expected_program() {
+    let program = temp_file("read_io 1 write_io 1 halt");
+    let other_program = temp_file("read_io 1 push 1 add write_io 1 halt");
+
+    let dir = tempfile::tempdir().unwrap();
+    command_in_dir(&dir)
+        .args(["prove", "--program", program.path().to_str().unwrap()])
+        .args(["--input", "7"])
+        .assert()
+        .success();
+    command_in_dir(&dir)
+        .args([
+            "verify",
+            "--expect-program",
+            program.path().to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    let assert = command_in_dir(&dir)
+        .args(["--format", "json", "verify"])
+        .args(["--expect-program", other_program.path().to_str().unwrap()])
+        .assert()
+        .failure();
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!("claim_mismatch", report["error"]["kind"]);
+}
+
+#[test]
+fn verify_with_expected_input_and_output() {
+    let program = temp_file("read_io 2 add write_io 1 halt");
+
+    let dir = tempfile::tempdir().unwrap();
+    command_in_dir(&dir)
+        .args(["prove", "--program", program.path().to_str().unwrap()])
+        .args(["--input", "3,4"])
+        .assert()
+        .success();
+    let expected_output = temp_file("7");
+    command_in_dir(&dir)
+        .args(["verify", "--expect-input", "3, 4"])
+        .args([
+            "--expect-output-file",
+            expected_output.path().to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    command_in_dir(&dir)
+        .args(["verify", "--expect-input", "4,3"])
+        .assert()
+        .stderr(predicates::str::contains(
+            "the claim's public input is [3, 4], but [4, 3] was expected",
+        ))
+        .failure();
+    command_in_dir(&dir)
+        .args(["verify", "--expect-output", ""])
+        .assert()
+        .stderr(predicates::str::contains(
+            "the claim's public output is [7], but [] was expected",
+        ))
+        .failure();
+
+    command_in_dir(&dir)
+        .args(["verify", "--input-format", "json"])
+        .args(["--expect-input", "[3, 4]", "--expect-output", "[7]"])
+        .assert()
+        .success();
+    command_in_dir(&dir)
+        .args(["verify", "--input-format", "hex"])
+        .args(["--expect-input", "0x3 0x4", "--expect-output", "0x8"])
+        .assert()
+        .stderr(predicates::str::contains(
+            "the claim's public output is [7], but [8] was expected",
+        ))
+        .failure();
+}
+
+#[test]
+fn verify_batch_from_directory_and_manifest() {
+    let dir = tempfile::tempdir().unwrap();
+    for (name, program) in [
+        ("a", "push 1 write_io 1 halt"),
+        ("b", "push 2 write_io 1 halt"),
+    ] {
+        let program = temp_file(program);
+        command_in_dir(&dir)
+            .args(["prove", "--program", program.path().to_str().unwrap()])
+            .args(["--claim", &format!("{name}.claim")])
+            .args(["--proof", &format!("{name}.proof")])
+            .assert()
+            .success();
+    }
+
+    command_in_dir(&dir)
+        .args(["verify", "--batch", "."])
+        .assert()
+        .stdout(predicates::str::ends_with("2 of 2 pairs verified\n"))
+        .success();
+    command_in_dir(&dir)
+        .args(["verify", "--batch", ".", "--expect-output", "1"])
+        .assert()
+        .stdout(predicates::str::contains(
+            "b.proof: error: the claim's public output",
+        ))
+        .stdout(predicates::str::ends_with("1 of 2 pairs verified\n"))
+        .failure();
+
+    std::fs::write(dir.path().join("d.proof"), b"corrupt").unwrap();
+    let manifest = r#"[{"proof":"a.proof","claim":"b.claim"},{"proof":"b.proof"},
+        {"proof":"c.proof"},{"proof":"d.proof"}]"#;
+    std::fs::write(dir.path().join("manifest.json"), manifest).unwrap();
+    let assert = command_in_dir(&dir)
+        .args(["--format", "json", "verify", "--batch", "manifest.json"])
+        .assert()
+        .failure();
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!("verify_batch", report["command"]);
+    assert_eq!(4, report["num_pairs"]);
+    assert_eq!(1, report["num_verified"]);
+    let pairs = &report["pairs"];
+    assert_eq!(false, pairs[0]["verdict"]);
+    assert!(pairs[0]["error"].is_null());
+    assert_eq!(true, pairs[1]["verdict"]);
+    assert_eq!("io", pairs[2]["error"]["kind"]);
+    assert_eq!("deserialization", pairs[3]["error"]["kind"]);
+    let message = pairs[3]["error"]["message"].as_str().unwrap();
+    assert!(
+        message.contains("“d.proof” is not a proof container"),
+        "{message}"
+    );
+
+    let assert = command_in_dir(&dir)
+        .args(["--format", "json", "verify", "--batch", "missing.json"])
+        .assert()
+        .code(6);
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!("verify_batch", report["command"]);
+}
+
+#[test]
+fn prove_and_verify_values_using_the_library() {
+    let program = triton_program!(read_io 1 write_io 1 halt);
+    let input = || PublicInput::new(bfe_vec![7]);
+    let non_determinism = NonDeterminism::default;
+    let limits = Limits::default();
+
+    let report =
+        triton_cli::run_program(program.clone(), input(), non_determinism(), limits, None).unwrap();
+    assert_eq!(bfe_vec![7], report.output);
+
+    let (claim, proof) = triton_cli::prove_program(
+        program.clone(),
+        input(),
+        non_determinism(),
+        limits,
+        Stark::default(),
+    )
+    .unwrap();
+    assert_eq!(program.hash(), claim.program_digest);
+    assert_eq!(bfe_vec![7], claim.output);
+
+    let mut expected = ExpectedClaim {
+        output: Some(bfe_vec![7]),
+        ..ExpectedClaim::default()
+    };
+    let verdict = triton_cli::verify_proof(&claim, &proof, Stark::default(), &expected);
+    assert!(verdict.unwrap());
+    expected.output = Some(bfe_vec![8]);
+    let verdict = triton_cli::verify_proof(&claim, &proof, Stark::default(), &expected);
+    assert!(verdict.is_err());
+
+    let report = triton_cli::inspect_proof(claim, Some((&proof, Stark::default()))).unwrap();
+    assert_eq!(1, report.claim.output_len);
+    assert!(report.proof.unwrap().padded_height.is_some());
+}
+
+#[test]
+fn prove_and_verify_files_using_the_library() {
+    let dir = tempfile::tempdir().unwrap();
+    let program = dir.path().join("program.tasm");
+    std::fs::write(&program, "read_io 1 write_io 1 halt").unwrap();
+    let program = program.to_str().unwrap().to_string();
+
+    let mut input = InputArgs::default();
+    input.input = Some("7".to_string());
+    let mut args = RunArgs::default();
+    args.separate_files.program = Some(program.clone());
+    args.separate_files.public_input = Some(input);
+    let path = |name| dir.path().join(name).to_str().unwrap().to_string();
+    let mut artifacts = ProofArtifacts::default();
+    artifacts.claim = Some(path("triton.claim"));
+    artifacts.proof = path("triton.proof");
+    artifacts.parameters = path("triton.parameters");
+    let report = triton_cli::prove(args, StarkArgs::default(), artifacts.clone(), None).unwrap();
+    assert_eq!(bfe_vec![7], report.output);
+
+    let (claim, _, stark) = artifacts.read().unwrap();
+    assert_eq!(Stark::default(), stark);
+    assert_eq!(
+        triton_cli::digest(program).unwrap().program_digest,
+        claim.program_digest
+    );
+
+    let report = triton_cli::verify(artifacts, Expectations::default(), None).unwrap();
+    assert!(report.verdict);
+}
+
+#[test]
+fn failures_have_distinct_exit_codes() {
+    let halt = temp_file("halt");
+    let halt = halt.path().to_str().unwrap();
+    let unparsable = temp_file("no_such_instruction");
+    let crashing = temp_file("push 0 assert halt");
+    let run = |program: &std::path::Path| {
+        let mut command = command();
+        command.args(["run", "--program", program.to_str().unwrap()]);
+        command
+    };
+
+    command().arg("run").assert().code(2);
+    run("no_such_file.tasm".as_ref()).assert().code(6);
+    run(unparsable.path()).assert().code(7);
+    run(halt.as_ref()).args(["--input", "x"]).assert().code(8);
+    run(crashing.path()).assert().code(10);
+    command()
+        .args(["prove", "--program", halt])
+        .args(["--fri-expansion-factor", "3"])
+        .assert()
+        .code(2);
+
+    let dir = tempfile::tempdir().unwrap();
+    let program = temp_file("push 1 write_io 1 halt");
+    command_in_dir(&dir)
+        .args(["prove", "--program", program.path().to_str().unwrap()])
+        .assert()
+        .success();
+    command_in_dir(&dir)
+        .args(["verify", "--expect-output", "2"])
+        .assert()
+        .code(5);
+
+    // a claim about a different program
+    let claim = Claim::about_program(&triton_program!(halt));
+    let claim = serde_json::to_string(&claim).unwrap();
+    std::fs::write(dir.path().join("triton.claim"), claim).unwrap();
+    command_in_dir(&dir).arg("verify").assert().success();
+    command_in_dir(&dir)
+        .args(["verify", "--claim", "triton.claim"])
+        .assert()
+        .code(4);
+
+    std::fs::write(dir.path().join("triton.claim"), "not json").unwrap();
+    command_in_dir(&dir)
+        .args(["verify", "--claim", "triton.claim"])
+        .assert()
+        .code(9);
+}
+
+#[test]
+fn program_parse_errors_point_at_source_with_suggestions() {
+    let program = temp_file("push 1\npussh 2\ncall fooo\nhalt\nfoo:\n  return\n");
+    let program_path = program.path().to_str().unwrap();
+
+    command()
+        .args(["run", "--program", program_path])
+        .assert()
+        .stderr(predicates::str::contains(format!(
+            "--> {program_path}:2:1\n"
+        )))
+        .stderr(predicates::str::contains("2 | pussh 2\n  | ^^^^^\n"))
+        .stderr(predicates::str::contains(
+            "help: did you mean instruction “push”?",
+        ))
+        .stderr(predicates::str::contains("\x1b[").not())
+        .code(7);
+
+    let program = temp_file("push 1\ncall fooo\nhalt\nfoo:\n  return\n");
+    command()
+        .args(["--color", "always", "run"])
+        .args(["--program", program.path().to_str().unwrap()])
+        .assert()
+        .stderr(predicates::str::contains("missing label"))
+        .stderr(predicates::str::contains("did you mean “foo”?"))
+        .stderr(predicates::str::contains("\x1b["))
+        .code(7);
+}
+
+#[test]
+fn program_parse_error_without_diagnostics_falls_back_to_parser_message() {
+    let error = ProgramParseError {
+        file: "program.tasm".to_string(),
+        diagnostics: vec![],
+        message: "something is off".to_string(),
+    };
+    let rendered = error.render(true);
+    assert!(rendered.contains("program.tasm"));
+    assert!(rendered.contains("something is off"));
+    assert_eq!(rendered, error.to_string());
+}
+
+#[test]
+fn read_program_and_input_from_stdin() {
+    command()
+        .args(["run", "--program", "-", "--input", "3,4"])
+        .write_stdin("read_io 2 add write_io 1 halt")
+        .assert()
+        .stdout("7\n")
+        .success();
+
+    let program = temp_file("read_io 2 mul write_io 1 halt");
+    command()
+        .args(["run", "--program", program.path().to_str().unwrap()])
+        .args(["--input-file", "-"])
+        .write_stdin("5,6")
+        .assert()
+        .stdout("30\n")
+        .success();
+
+    command()
+        .args(["run", "--program", "-", "--input-file", "-"])
+        .write_stdin("halt")
+        .assert()
+        .stderr(predicates::str::contains("at most one file"))
+        .code(2);
+
+    command()
+        .args(["run", "--program", "-"])
+        .write_stdin("pussh 1")
+        .assert()
+        .stderr(predicates::str::contains("--> <stdin>:1:1"))
+        .code(7);
+}
+
+#[test]
+fn stream_proof_from_prove_to_verify_and_inspect() {
+    let program = temp_file("read_io 2 add write_io 1 halt");
+
+    let dir = tempfile::tempdir().unwrap();
+    let assert = command_in_dir(&dir)
+        .args(["prove", "--program", program.path().to_str().unwrap()])
+        .args(["--input", "3,4", "--proof", "-"])
+        .assert()
+        .success();
+    let proof = assert.get_output().stdout.clone();
+    assert!(!dir.path().join("triton.proof").exists());
+
+    std::fs::remove_file(dir.path().join("triton.claim")).unwrap();
+    command_in_dir(&dir)
+        .args(["verify", "--proof", "-", "--expect-output", "7"])
+        .write_stdin(proof.clone())
+        .assert()
+        .success();
+    command_in_dir(&dir)
+        .args(["inspect", "--proof", "-"])
+        .write_stdin(proof)
+        .assert()
+        .stdout(predicates::str::contains("output:               7\n"))
+        .success();
+}
+
+#[test]
+fn conflicting_uses_of_stdin_and_stdout_fail() {
+    let program = temp_file("halt");
+    let program = program.path().to_str().unwrap();
+
+    command()
+        .args([
+            "prove",
+            "--program",
+            program,
+            "--claim",
+            "-",
+            "--proof",
+            "-",
+        ])
+        .assert()
+        .code(2);
+    command()
+        .args([
+            "--format",
+            "json",
+            "prove",
+            "--program",
+            program,
+            "--proof",
+            "-",
+        ])
+        .assert()
+        .code(2);
+    command()
+        .args(["debug", "--program", "-"])
+        .write_stdin("halt")
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn run_program_with_input_in_various_formats() {
+    let program = temp_file("read_io 3 write_io 3 halt");
+    let program_path = program.path().to_str().unwrap();
+    let run = |format: &str, input: &str| {
+        let mut command = command();
+        command.args(["run", "--program", program_path]).args([
+            "--input-format",
+            format,
+            "--input",
+            input,
+        ]);
+        command
+    };
+
+    run("decimal", "1 2\n3")
+        .assert()
+        .stdout("3, 2, 1\n")
+        .success();
+    run("decimal", "-1, 0, 1")
+        .assert()
+        .stdout("1, 0, -1\n")
+        .success();
+    run("hex", "0x10, ff A")
+        .assert()
+        .stdout("10, 255, 16\n")
+        .success();
+    run("json", r#"[1, "-2", 3]"#)
+        .assert()
+        .stdout("3, -2, 1\n")
+        .success();
+    run("xfe", "(1, 2, 3)")
+        .assert()
+        .stdout("1, 2, 3\n")
+        .success();
+    run("u32", "0, 1\n255")
+        .assert()
+        .stdout("255, 1, 0\n")
+        .success();
+    run("u32", "4294967295 0 0").assert().success();
+
+    run("json", "{}").assert().code(8);
+    run("xfe", "(1, 2)").assert().code(8);
+    run("hex", "0xffffffffffffffff").assert().code(8);
+    run("u32", "4294967296").assert().code(8);
+    run("u32", "-1, 0, 1")
+        .assert()
+        .stderr(predicates::str::contains("“-1” is not a u32"))
+        .code(8);
+}
+
+#[test]
+fn digest_can_be_used_as_input() {
+    let program = temp_file("read_io 5 write_io 5 halt");
+    let program_path = program.path().to_str().unwrap();
+
+    let assert = command()
+        .args(["digest", "--program", program_path])
+        .assert()
+        .success();
+    let digest = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    let expected_output = Claim::about_program(&triton_program!(read_io 5 write_io 5 halt))
+        .program_digest
+        .values()
+        .iter()
+        .join(", ");
+
+    command()
+        .args(["run", "--program", program_path])
+        .args(["--input-format", "digest", "--input", digest.trim()])
+        .assert()
+        .stdout(format!("{expected_output}\n"))
+        .success();
+}
+
+#[test]
+fn run_program_with_non_determinism_from_separate_files() {
+    let program = temp_file(triton_program!(
+        divine 2 add write_io 1
+        push 7 read_mem 1 pop 1 write_io 1
+        push 2 push 0 push 0 push 0 push 0 push 0 merkle_step
+        halt
+    ));
+    let program_path = program.path().to_str().unwrap();
+    let secret_input = temp_file("3 4");
+    let digest = temp_file(format!("{:x}", triton_program!(halt).hash()));
+    let ram = temp_file("7: 42\n\n8: 43\n");
+
+    command()
+        .args(["run", "--program", program_path])
+        .args(["--secret-input", secret_input.path().to_str().unwrap()])
+        .args(["--secret-digests", digest.path().to_str().unwrap()])
+        .args(["--ram", ram.path().to_str().unwrap()])
+        .assert()
+        .stdout("7, 42\n")
+        .success();
+
+    // the separate files are added to the monolithic non-determinism
+    let non_determinism = NonDeterminism::new(bfe_vec![3]);
+    let non_determinism = temp_file(serde_json::to_string(&non_determinism).unwrap());
+    let secret_input = temp_file("4");
+    command()
+        .args(["run", "--program", program_path])
+        .args([
+            "--non-determinism",
+            non_determinism.path().to_str().unwrap(),
+        ])
+        .args(["--secret-input", secret_input.path().to_str().unwrap()])
+        .args(["--secret-digests", digest.path().to_str().unwrap()])
+        .args(["--ram", ram.path().to_str().unwrap()])
+        .assert()
+        .stdout("7, 42\n")
+        .success();
+
+    // an address must not be set by both sources
+    let non_determinism = NonDeterminism::default().with_ram(HashMap::from([(bfe!(8), bfe!(1))]));
+    let non_determinism = temp_file(serde_json::to_string(&non_determinism).unwrap());
+    command()
+        .args(["run", "--program", program_path])
+        .args([
+            "--non-determinism",
+            non_determinism.path().to_str().unwrap(),
+        ])
+        .args(["--ram", ram.path().to_str().unwrap()])
+        .assert()
+        .stderr(predicates::str::contains("RAM address 8 is set by both"))
+        .code(2);
+
+    let ram = temp_file("7 42");
+    command()
+        .args(["run", "--program", program_path])
+        .args(["--ram", ram.path().to_str().unwrap()])
+        .assert()
+        .stderr(predicates::str::contains("address: value"))
+        .code(8);
+}
+
+#[test]
+fn run_program_with_output_in_various_formats() {
+    let program = temp_file("read_io 5 write_io 5 halt");
+    let program_path = program.path().to_str().unwrap();
+    let digest = format!("{:x}", triton_program!(halt).hash());
+    let run = |format: &str, input: &str| {
+        let mut command = command();
+        command.args(["run", "--program", program_path]).args([
+            "--input",
+            input,
+            "--output-format",
+            format,
+        ]);
+        command
+    };
+
+    run("decimal", "5 4 3 2 1")
+        .assert()
+        .stdout("1, 2, 3, 4, 5\n");
+    run("lines", "5 4 3 2 -1")
+        .assert()
+        .stdout("-1\n2\n3\n4\n5\n");
+    run("hex", "5 4 3 2 255")
+        .assert()
+        .stdout("0xff\n0x2\n0x3\n0x4\n0x5\n");
+    run("json", "5 4 3 2 -1")
+        .assert()
+        .stdout("[18446744069414584320,2,3,4,5]\n");
+    run("xfe", "1 2 3 4 5").assert().code(2);
+    command()
+        .args(["run", "--program", program_path])
+        .args(["--input-format", "digest", "--input", &digest])
+        .args(["--output-format", "digest"])
+        .assert()
+        .stdout(format!("{digest}\n"))
+        .success();
+
+    let program = temp_file("read_io 3 write_io 3 halt");
+    let dir = tempfile::tempdir().unwrap();
+    command_in_dir(&dir)
+        .args(["run", "--program", program.path().to_str().unwrap()])
+        .args(["--input-format", "xfe", "--input", "(1, 2, 3)"])
+        .args(["--output-format", "xfe", "--output-file", "output.txt"])
+        .assert()
+        .stdout("")
+        .success();
+    let output = std::fs::read_to_string(dir.path().join("output.txt")).unwrap();
+    assert_eq!("(1, 2, 3)\n", output);
+
+    // failing to format the output is reported instead of the report, not after it
+    let program = temp_file("push 1 write_io 1 halt");
+    let assert = command_in_dir(&dir)
+        .args(["--format", "json", "run"])
+        .args(["--program", program.path().to_str().unwrap()])
+        .args(["--output-format", "xfe", "--output-file", "ungrouped.txt"])
+        .assert()
+        .code(2);
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert_eq!(1, stdout.lines().count());
+    assert!(stdout.contains(r#""kind":"invalid_argument""#));
+    assert!(!dir.path().join("ungrouped.txt").exists());
+}
+
+#[test]
+fn run_program_with_coverage() {
+    let program = temp_file(
+        "read_io 1 call loop push 0 skiz call never halt\n\
+         loop:\n  dup 0 push 0 eq skiz return\n  addi -1 recurse\n\
+         never:\n  push 0 assert return\n",
+    );
+    let program_path = program.path().to_str().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    command_in_dir(&dir)
+        .args(["run", "--program", program_path, "--input", "3"])
+        .args(["--coverage", "coverage.info"])
+        .assert()
+        .success();
+    let lcov = fs_err::read_to_string(dir.path().join("coverage.info")).unwrap();
+    let expected = format!(
+        "TN:\nSF:{program_path}\n\
+         FN:2,loop\nFN:5,never\nFNDA:4,loop\nFNDA:0,never\nFNF:2\nFNH:1\n\
+         BRDA:1,6,0,0\nBRDA:1,6,1,1\nBRDA:3,15,0,1\nBRDA:3,15,1,3\nBRF:4\nBRH:3\n\
+         DA:1,1\nDA:3,4\nDA:4,3\nDA:6,0\nLF:4\nLH:3\nend_of_record\n"
+    );
+    assert_eq!(expected, lcov);
+
+    let assert = command_in_dir(&dir)
+        .args(["--format", "json", "run", "--program", program_path])
+        .args(["--input", "0", "--coverage", "coverage.info"])
+        .assert()
+        .success();
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let coverage = &report["coverage"];
+    assert_eq!(program_path, coverage["file"]);
+    assert_eq!(4, coverage["lines_found"]);
+    assert_eq!(2, coverage["lines_hit"]);
+    assert_eq!(2, coverage["labels_found"]);
+    assert_eq!(1, coverage["labels_hit"]);
+    assert_eq!(4, coverage["branches_found"]);
+    assert_eq!(2, coverage["branches_hit"]);
+
+    command_in_dir(&dir)
+        .args(["run", "--program", "-", "--coverage", "coverage.info"])
+        .write_stdin("halt")
+        .assert()
+        .code(2);
+    command_in_dir(&dir)
+        .args(["run", "--initial-state", "state.json"])
+        .args(["--coverage", "coverage.info"])
+        .assert()
+        .code(2);
+
+    let crashing_program = temp_file("push 1 write_io 1\npush 0\nassert\nhalt\n");
+    let crashing_program_path = crashing_program.path().to_str().unwrap();
+    command_in_dir(&dir)
+        .args(["run", "--program", crashing_program_path])
+        .args(["--coverage", "crash.info"])
+        .args(["--dump-state-on-error", "crash.json"])
+        .assert()
+        .failure();
+    let lcov = fs_err::read_to_string(dir.path().join("crash.info")).unwrap();
+    assert!(lcov.contains("DA:1,1\nDA:2,1\nDA:3,1\nDA:4,0\n"), "{lcov}");
+    assert!(dir.path().join("crash.json").exists());
+}
+
+#[test]
+fn trace_program_execution() {
+    let program = temp_file("push 2 call foo halt\nfoo: push 3 mul write_io 1 return\n");
+    let program_path = program.path().to_str().unwrap();
+
+    let assert = command()
+        .args(["trace", "--program", program_path])
+        .assert()
+        .success();
+    let trace = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    let lines = trace.lines().collect_vec();
+    assert_eq!(8, lines.len());
+    assert!(lines[0].starts_with("cycle,instruction_pointer,instruction,"));
+    assert!(lines[0].ends_with(",st14,st15"));
+    assert!(lines[2].starts_with("1,2,call foo,0,17,2,"));
+    assert!(lines[4].starts_with("3,7,mul,1,18,3,2,"));
+    assert!(lines[7].starts_with("6,4,halt,0,16,"));
+
+    let assert = command()
+        .args([
+            "trace",
+            "--program",
+            program_path,
+            "--trace-format",
+            "jsonl",
+        ])
+        .assert()
+        .success();
+    let trace = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    let rows = trace.lines().collect_vec();
+    assert_eq!(7, rows.len());
+    let row: serde_json::Value = serde_json::from_str(rows[4]).unwrap();
+    assert_eq!("write_io 1", row["instruction"]);
+    assert_eq!(6, row["op_stack"][0]);
+    assert_eq!(1, row["jump_stack_depth"]);
+}
+
+#[test]
+fn trace_of_crashing_program_ends_with_failing_instruction() {
+    let program = temp_file("push 0 assert halt");
+    let dir = tempfile::tempdir().unwrap();
+
+    let assert = command_in_dir(&dir)
+        .args(["--format", "json", "trace"])
+        .args(["--program", program.path().to_str().unwrap()])
+        .args(["--trace-file", "trace.csv"])
+        .assert()
+        .code(10);
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!("trace", report["command"]);
+
+    let trace = std::fs::read_to_string(dir.path().join("trace.csv")).unwrap();
+    assert!(trace.lines().last().unwrap().starts_with("1,2,assert,"));
+}
+
+#[test]
+fn trace_of_unparsable_program_leaves_no_trace_file() {
+    let program = temp_file("push 0 frobnicate halt");
+    let dir = tempfile::tempdir().unwrap();
+
+    command_in_dir(&dir)
+        .args(["trace", "--program", program.path().to_str().unwrap()])
+        .args(["--trace-file", "trace.csv"])
+        .assert()
+        .code(7);
+    assert!(!dir.path().join("trace.csv").exists());
+}
+
+#[test]
+fn aet_tables_have_triton_vm_heights() {
+    let program = Program::from_code(
+        "push 3 push 17 split pop 2
+         push 5 push 3 lt pop 1 push 0 push 0 lt pop 1
+         push 12 push 10 and pop 1 push 12 push 10 xor pop 1
+         push 100 log_2_floor pop 1 push 5 push 3 pow pop 1
+         push 7 push 23 div_mod pop 2 push 255 pop_count pop 1
+         push 1 push 1 push 1 push 1 push 1 hash
+         halt",
+    )
+    .unwrap();
+    let (aet, _) = VM::trace_execution(program, [].into(), [].into()).unwrap();
+
+    let tables = triton_cli::aet::tables(&aet);
+    for table in &tables {
+        assert_eq!(
+            aet.height_of_table(table.id),
+            table.rows.len(),
+            "{}",
+            table.name
+        );
+    }
+
+    let u32_table = tables.iter().find(|table| table.name == "u32").unwrap();
+    let column = |name| u32_table.columns.iter().position(|c| c == name).unwrap();
+    let [copy_flag, ci, lhs, rhs, result] = ["CopyFlag", "CI", "LHS", "RHS", "Result"].map(column);
+    let opcode = |instruction: Instruction| instruction.opcode_b();
+    for row in u32_table
+        .rows
+        .iter()
+        .filter(|row| row[copy_flag] == bfe!(1))
+    {
+        let (l, r) = (row[lhs].value(), row[rhs].value());
+        let expected = match row[ci] {
+            ci if ci == opcode(Instruction::Split) => 0,
+            ci if ci == opcode(Instruction::Lt) => u64::from(l < r),
+            ci if ci == opcode(Instruction::And) => l & r,
+            ci if ci == opcode(Instruction::Log2Floor) => u64::from(l.ilog2()),
+            ci if ci == opcode(Instruction::Pow) => l.pow(r.try_into().unwrap()),
+            ci if ci == opcode(Instruction::PopCount) => u64::from(l.count_ones()),
+            ci => panic!("unexpected instruction {ci}"),
+        };
+        assert_eq!(bfe!(expected), row[result], "{row:?}");
+    }
+}
+
+#[test]
+fn export_aet_tables() {
+    let program =
+        temp_file("push 2 call foo push 5 push 3 lt pop 1 halt\nfoo: push 3 mul return\n");
+    let program_path = program.path().to_str().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    command_in_dir(&dir)
+        .args(["aet", "--program", program_path])
+        .assert()
+        .stdout(predicates::str::contains("Processor:     10\n"))
+        .stdout(predicates::str::contains("U32:           4\n"))
+        .stdout(predicates::str::contains("padded height: 256"))
+        .success();
+    let jump_stack = std::fs::read_to_string(dir.path().join("aet/jump_stack.csv")).unwrap();
+    let jump_stack = jump_stack.lines().collect_vec();
+    assert_eq!("CLK,CI,JSP,JSO,JSD", jump_stack[0]);
+    assert_eq!(11, jump_stack.len());
+    assert!(jump_stack[10].starts_with("4,"));
+    let u32_table = std::fs::read_to_string(dir.path().join("aet/u32.csv")).unwrap();
+    let u32_table = u32_table.lines().collect_vec();
+    assert_eq!(
+        "CopyFlag,Bits,BitsMinus33Inv,CI,LHS,LhsInv,RHS,RhsInv,Result,LookupMultiplicity",
+        u32_table[0]
+    );
+    assert_eq!(5, u32_table.len());
+
+    let assert = command_in_dir(&dir)
+        .args(["--format", "json", "aet", "--program", program_path])
+        .args(["--aet-format", "binary", "--aet-dir", "binary"])
+        .assert()
+        .success();
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let manifest = std::fs::read_to_string(dir.path().join("binary/manifest.json")).unwrap();
+    let manifest: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+    assert_eq!(report["files"], manifest);
+    for file in manifest.as_array().unwrap() {
+        let path = dir.path().join(file["file"].as_str().unwrap());
+        let num_elements =
+            file["num_rows"].as_u64().unwrap() * file["columns"].as_array().unwrap().len() as u64;
+        assert_eq!(8 * num_elements, std::fs::metadata(path).unwrap().len());
+    }
+}